@@ -1,5 +1,5 @@
-use crate::actions::{set_movement_actions, Actions};
 use crate::loading::AudioAssets;
+use crate::menu::OwareCfg;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
@@ -10,45 +10,35 @@ pub struct InternalAudioPlugin;
 impl Plugin for InternalAudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(AudioPlugin)
-            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(start_audio))
-            .add_system_set(
-                SystemSet::on_update(GameState::Playing)
-                    .with_system(control_flying_sound.after(set_movement_actions)),
-            );
+            .add_event::<Sound>()
+            .add_system(play_sounds.run_if(not(in_state(GameState::Loading))));
     }
 }
 
-#[derive(Resource)]
-struct FlyingAudio(Handle<AudioInstance>);
-
-fn start_audio(mut commands: Commands, audio_assets: Res<AudioAssets>, audio: Res<Audio>) {
-    audio.pause();
-    let handle = audio
-        .play(audio_assets.flying.clone())
-        .looped()
-        .with_volume(0.3)
-        .handle();
-    commands.insert_resource(FlyingAudio(handle));
+/// Sound effects the board asks for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sound {
+    /// A seed landed in a bowl
+    Seed,
+    /// Seeds were taken from the board into a store
+    Capture,
+    GameEnd,
 }
 
-fn control_flying_sound(
-    actions: Res<Actions>,
-    audio: Res<FlyingAudio>,
-    mut audio_instances: ResMut<Assets<AudioInstance>>,
+fn play_sounds(
+    mut sounds: EventReader<Sound>,
+    cfg: Res<OwareCfg>,
+    assets: Res<AudioAssets>,
+    audio: Res<Audio>,
 ) {
-    if let Some(instance) = audio_instances.get_mut(&audio.0) {
-        match instance.state() {
-            PlaybackState::Paused { .. } => {
-                if actions.player_movement.is_some() {
-                    instance.resume(AudioTween::default());
-                }
-            }
-            PlaybackState::Playing { .. } => {
-                if actions.player_movement.is_none() {
-                    instance.pause(AudioTween::default());
-                }
-            }
-            _ => {}
-        }
+    // the master volume from the settings scales each effect's own level
+    let master = if cfg.muted { 0. } else { cfg.volume as f64 / 100. };
+    for sound in sounds.iter().filter(|_| master > 0.) {
+        let (source, volume) = match sound {
+            Sound::Seed => (&assets.seed, 0.4),
+            Sound::Capture => (&assets.capture, 0.8),
+            Sound::GameEnd => (&assets.game_end, 1.),
+        };
+        audio.play(source.clone()).with_volume(master * volume);
     }
 }
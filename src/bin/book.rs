@@ -0,0 +1,141 @@
+//! Builds an opening book from self-play games
+//!
+//! ```text
+//! cargo run --release --bin book -- [OPTIONS]
+//! ```
+//!
+//! The bot plays `--games` games against itself, and every move of the first `--plies`
+//! plies goes into the book, weighted by how the game went for the side that played it:
+//! 2 for a win, 1 for a draw and nothing for a loss.
+use std::process::exit;
+
+use board_game::board::{Board, Outcome};
+use oware::{seeds_range, Actor, Ai, Book, OwareBoard, Rules, PITS};
+
+const USAGE: &str = "\
+usage: book [OPTIONS]
+
+options:
+    --bot SPEC   bot playing the games, default mcts:729:2
+    --games N    games to play, default 100
+    --plies N    plies of each game kept in the book, default 8
+    --pits N     pits per side, default 6
+    --seeds N    initial seeds per pit, default 4
+    --rules R    Abapa, GrandSlamForbidden, GrandSlamToOpponent or Kalah
+    --out PATH   file to write, default oware-book.txt";
+
+struct Options {
+    bot: Ai,
+    games: usize,
+    plies: usize,
+    pits: usize,
+    seeds: u8,
+    rules: Rules,
+    out: String,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Self {
+            bot: Ai::Mcts(729, 2),
+            games: 100,
+            plies: 8,
+            pits: 6,
+            seeds: 4,
+            rules: Rules::default(),
+            out: Book::FILE.to_string(),
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            let invalid = |v: &str| format!("invalid {arg} {v:?}");
+            match arg.as_str() {
+                "--bot" => opts.bot = value()?.parse()?,
+                "--games" => {
+                    let v = value()?;
+                    opts.games = v.parse().map_err(|_| invalid(&v))?
+                }
+                "--plies" => {
+                    let v = value()?;
+                    opts.plies = v.parse().map_err(|_| invalid(&v))?
+                }
+                "--pits" => {
+                    let v = value()?;
+                    opts.pits = v
+                        .parse()
+                        .ok()
+                        .filter(|p| PITS.contains(p))
+                        .ok_or_else(|| invalid(&v))?
+                }
+                "--seeds" => {
+                    let v = value()?;
+                    opts.seeds = v.parse().map_err(|_| invalid(&v))?
+                }
+                "--rules" => opts.rules = value()?.parse()?,
+                "--out" => opts.out = value()?,
+                "-h" | "--help" => return Err(String::new()),
+                arg => return Err(format!("unknown option {arg:?}")),
+            }
+        }
+        let range = seeds_range(opts.pits);
+        if !range.contains(&opts.seeds) {
+            return Err(format!(
+                "invalid --seeds {}, expected {} to {}",
+                opts.seeds,
+                range.start(),
+                range.end()
+            ));
+        }
+        Ok(opts)
+    }
+}
+
+fn build<const P: usize>(opts: &Options) -> Book {
+    let mut book = Book::new(P, opts.seeds, opts.rules);
+    for game in 0..opts.games {
+        let mut board = OwareBoard::<P>::with_rules(opts.seeds, opts.rules);
+        let mut opening = vec![];
+        while !board.is_done() {
+            let mv = Actor::Bot(opts.bot).get_mv(&board).expect("bots always pick a move");
+            if opening.len() < opts.plies {
+                opening.push((board.clone(), mv));
+            }
+            board.play(mv);
+        }
+        for (position, mv) in opening {
+            let weight = match board.outcome() {
+                Some(Outcome::WonBy(p)) if p == position.next_player() => 2,
+                Some(Outcome::Draw) => 1,
+                _ => 0,
+            };
+            book.add(&position, mv, weight);
+        }
+        eprintln!("game {}: {} positions", game + 1, book.len());
+    }
+    book
+}
+
+fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            exit(2)
+        }
+    };
+    let book = match opts.pits {
+        3 => build::<3>(&opts),
+        4 => build::<4>(&opts),
+        5 => build::<5>(&opts),
+        6 => build::<6>(&opts),
+        7 => build::<7>(&opts),
+        8 => build::<8>(&opts),
+        9 => build::<9>(&opts),
+        10 => build::<10>(&opts),
+        _ => unreachable!("pits are checked against PITS"),
+    };
+    if let Err(e) = std::fs::write(&opts.out, book.to_string()) {
+        eprintln!("Could not write {}: {e}", opts.out);
+        exit(1)
+    }
+    println!("Wrote {} positions to {}", book.len(), opts.out);
+}
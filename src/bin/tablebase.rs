@@ -0,0 +1,88 @@
+//! Generates the endgame tablebase the `endgame` bots play from
+//!
+//! ```text
+//! cargo run --release --bin tablebase -- [OPTIONS]
+//! ```
+//!
+//! Every position of the 6 pit board with up to `--seeds` seeds left is solved, and the
+//! table is written where the game looks for it, unless `--out` says otherwise.
+use std::process::exit;
+use std::time::Instant;
+
+use oware::{Rules, Tablebase};
+
+const USAGE: &str = "\
+usage: tablebase [OPTIONS]
+
+options:
+    --seeds N    most seeds on the board, default 12, at most 16
+    --rules R    Abapa, GrandSlamForbidden or GrandSlamToOpponent
+    --out PATH   file to write, default oware.tb";
+
+/// While solving, the positions with the most seeds keep their quiet moves at about 50
+/// bytes each: some 650 MB for the 13 million positions with 16 seeds, and every seed
+/// more takes two thirds again as much
+const MAX_SEEDS: u8 = 16;
+
+struct Options {
+    seeds: u8,
+    rules: Rules,
+    out: String,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Self {
+            seeds: 12,
+            rules: Rules::default(),
+            out: Tablebase::FILE.to_string(),
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            let invalid = |v: &str| format!("invalid {arg} {v:?}");
+            match arg.as_str() {
+                "--seeds" => {
+                    let v = value()?;
+                    opts.seeds = v
+                        .parse()
+                        .ok()
+                        .filter(|&s| s <= MAX_SEEDS)
+                        .ok_or_else(|| invalid(&v))?
+                }
+                "--rules" => {
+                    let v = value()?;
+                    opts.rules = v.parse()?;
+                    if opts.rules == Rules::Kalah {
+                        return Err(invalid(&v));
+                    }
+                }
+                "--out" => opts.out = value()?,
+                "-h" | "--help" => return Err(String::new()),
+                arg => return Err(format!("unknown option {arg:?}")),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            exit(2)
+        }
+    };
+    let start = Instant::now();
+    let table = Tablebase::generate::<6>(opts.rules, opts.seeds, |seeds, positions| {
+        eprintln!(
+            "{seeds:>2} seeds: {positions:>9} positions, {:.1?}",
+            start.elapsed()
+        )
+    });
+    if let Err(e) = table.save(&opts.out) {
+        eprintln!("Could not write {}: {e}", opts.out);
+        exit(1)
+    }
+    println!("Wrote {}", opts.out);
+}
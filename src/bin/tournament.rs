@@ -0,0 +1,192 @@
+//! Round-robin matches between bots, without rendering
+//!
+//! ```text
+//! cargo run --release --bin tournament -- [OPTIONS] BOT BOT...
+//! ```
+//!
+//! Bots are given as specs like `random`, `rollout:729`, `mcts:729:2` or `minmax:6`.
+//! Every pair of bots plays `--games` games, swapping sides after each one, and the
+//! totals are printed as a table with Elo estimates relative to the field.
+use std::process::exit;
+
+use board_game::board::{Board, Outcome, Player};
+use oware::{seeds_range, Actor, Ai, OwareBoard, Rules, PITS};
+
+const USAGE: &str = "\
+usage: tournament [OPTIONS] BOT BOT...
+
+options:
+    --games N    games per pair of bots, at least 1, default 10
+    --pits N     pits per side, default 6
+    --seeds N    initial seeds per pit, default 4
+    --rules R    Abapa, GrandSlamForbidden, GrandSlamToOpponent or Kalah";
+
+struct Options {
+    games: usize,
+    pits: usize,
+    seeds: u8,
+    rules: Rules,
+    bots: Vec<(String, Ai)>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Self {
+            games: 10,
+            pits: 6,
+            seeds: 4,
+            rules: Rules::default(),
+            bots: vec![],
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            let invalid = |v: &str| format!("invalid {arg} {v:?}");
+            match arg.as_str() {
+                "--games" => {
+                    let v = value()?;
+                    opts.games = v
+                        .parse()
+                        .ok()
+                        .filter(|&g| g > 0)
+                        .ok_or_else(|| invalid(&v))?
+                }
+                "--pits" => {
+                    let v = value()?;
+                    opts.pits = v
+                        .parse()
+                        .ok()
+                        .filter(|p| PITS.contains(p))
+                        .ok_or_else(|| invalid(&v))?
+                }
+                "--seeds" => {
+                    let v = value()?;
+                    opts.seeds = v.parse().map_err(|_| invalid(&v))?
+                }
+                "--rules" => opts.rules = value()?.parse()?,
+                "-h" | "--help" => return Err(String::new()),
+                spec => opts.bots.push((spec.to_string(), spec.parse()?)),
+            }
+        }
+        let range = seeds_range(opts.pits);
+        if !range.contains(&opts.seeds) {
+            return Err(format!(
+                "invalid --seeds {}, expected {} to {}",
+                opts.seeds,
+                range.start(),
+                range.end()
+            ));
+        }
+        if opts.bots.len() < 2 {
+            return Err("at least two bots are needed".to_string());
+        }
+        Ok(opts)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Tally {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    plies: usize,
+}
+impl Tally {
+    fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+    fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.) / self.games() as f64
+    }
+}
+
+/// Plays one game, returning the outcome and its length in plies
+fn play<const P: usize>(opts: &Options, bots: [Ai; 2]) -> (Option<Outcome>, usize) {
+    let mut board = OwareBoard::<P>::with_rules(opts.seeds, opts.rules);
+    let mut plies = 0;
+    while !board.is_done() {
+        let ai = bots[board.next_player().index() as usize];
+        let mv = Actor::Bot(ai).get_mv(&board).expect("bots always pick a move");
+        board.play(mv);
+        plies += 1;
+    }
+    (board.outcome(), plies)
+}
+
+fn round_robin<const P: usize>(opts: &Options) -> Vec<Tally> {
+    let n = opts.bots.len();
+    let mut tally = vec![Tally::default(); n];
+    for i in 0..n {
+        for j in i + 1..n {
+            for game in 0..opts.games {
+                let (a, b) = if game % 2 == 0 { (i, j) } else { (j, i) };
+                let (outcome, plies) = play::<P>(opts, [opts.bots[a].1, opts.bots[b].1]);
+                for (k, player) in [(a, Player::A), (b, Player::B)] {
+                    let t = &mut tally[k];
+                    t.plies += plies;
+                    match outcome {
+                        Some(Outcome::WonBy(p)) if p == player => t.wins += 1,
+                        Some(Outcome::Draw) => t.draws += 1,
+                        _ => t.losses += 1,
+                    }
+                }
+            }
+            eprintln!("{} vs {}: done", opts.bots[i].0, opts.bots[j].0);
+        }
+    }
+    tally
+}
+
+/// Rating differences that would predict each bot's score, centered on the field
+fn elo(tally: &[Tally]) -> Vec<f64> {
+    let ratings: Vec<f64> = tally
+        .iter()
+        .map(|t| {
+            let p = t.score().clamp(0.01, 0.99);
+            -400. * (1. / p - 1.).log10()
+        })
+        .collect();
+    let mean = ratings.iter().sum::<f64>() / ratings.len() as f64;
+    ratings.iter().map(|r| r - mean).collect()
+}
+
+fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            exit(2)
+        }
+    };
+    let tally = match opts.pits {
+        3 => round_robin::<3>(&opts),
+        4 => round_robin::<4>(&opts),
+        5 => round_robin::<5>(&opts),
+        6 => round_robin::<6>(&opts),
+        7 => round_robin::<7>(&opts),
+        8 => round_robin::<8>(&opts),
+        9 => round_robin::<9>(&opts),
+        10 => round_robin::<10>(&opts),
+        _ => unreachable!("pits are checked against PITS"),
+    };
+    let elo = elo(&tally);
+    let width = opts.bots.iter().map(|b| b.0.len()).max().unwrap_or(0).max(3);
+    println!(
+        "{:width$}  {:>5} {:>5} {:>5} {:>6} {:>6} {:>10}",
+        "Bot", "Win", "Draw", "Loss", "Score", "Elo", "Avg plies"
+    );
+    let mut order: Vec<usize> = (0..tally.len()).collect();
+    order.sort_by(|&a, &b| elo[b].total_cmp(&elo[a]));
+    for i in order {
+        let t = &tally[i];
+        println!(
+            "{:width$}  {:>5} {:>5} {:>5} {:>5.1}% {:>+6.0} {:>10.1}",
+            opts.bots[i].0,
+            t.wins,
+            t.draws,
+            t.losses,
+            100. * t.score(),
+            elo[i],
+            t.plies as f64 / t.games() as f64,
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use crate::menu::OwareCfg;
+use crate::oware::{seeds_range, Actor, Ai, GameRecord, Rules, Setup, Turn, PITS};
+use bevy::prelude::*;
+
+/// Game setup asked for at launch, applied over the saved settings
+#[derive(Resource, Default, Clone)]
+pub struct Launch {
+    pub pits: Option<usize>,
+    pub seeds: Option<u8>,
+    pub rules: Option<Rules>,
+    /// Bot facing the human
+    pub bot: Option<Ai>,
+    pub human_second: bool,
+    /// Game to continue, which also skips the menu
+    pub load: Option<GameRecord>,
+    /// Start a new game right away
+    pub skip_menu: bool,
+}
+
+impl Launch {
+    pub fn apply(&self, cfg: &mut OwareCfg) {
+        if let Some(pits) = self.pits.filter(|p| PITS.contains(p)) {
+            (cfg.pits, cfg.new_pits) = (pits, pits);
+        }
+        if let Some(seeds) = self.seeds {
+            if seeds_range(cfg.pits).contains(&seeds) {
+                cfg.init_seeds = seeds;
+            } else {
+                warn!("Ignoring {seeds} seeds per pit, too many for {} pits", cfg.pits);
+            }
+        }
+        if let Some(rules) = self.rules {
+            cfg.rules = rules;
+        }
+        if self.bot.is_some() || self.human_second {
+            let bot = self.bot.map(Actor::Bot).unwrap_or_else(|| {
+                let bots = cfg.actors.into_iter().find(|a| !a.is_human());
+                bots.unwrap_or(Actor::Bot(Ai::Random))
+            });
+            cfg.actors = if self.human_second {
+                [bot, Actor::Human]
+            } else {
+                [Actor::Human, bot]
+            };
+        }
+        if let Some(record) = &self.load {
+            cfg.pits = record.pits;
+        } else if self.skip_menu {
+            cfg.new_game = true;
+        }
+    }
+    /// Go straight to the board instead of the first menu
+    pub fn skips_menu(&self) -> bool {
+        self.skip_menu || self.load.is_some()
+    }
+    /// Reads a URL query like `pits=6&seeds=4&rules=Abapa&bot=minmax:6&human-second&moves=A3-B1`,
+    /// where a move list or a `setup` position continues that game and `skip-menu` starts a
+    /// new one
+    pub fn from_query(query: &str) -> Result<Self, String> {
+        let mut launch = Self::default();
+        let mut moves = None;
+        let mut setup = None;
+        for param in query.trim_start_matches('?').split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            let value = decode(value);
+            let invalid = || format!("invalid {key} {value:?}");
+            match key {
+                "pits" => {
+                    let pits = value.parse().ok().filter(|p| PITS.contains(p));
+                    launch.pits = Some(pits.ok_or_else(invalid)?)
+                }
+                "seeds" => launch.seeds = Some(value.parse().map_err(|_| invalid())?),
+                "rules" => launch.rules = Some(value.parse()?),
+                "bot" => launch.bot = Some(value.parse()?),
+                "human-second" => launch.human_second = true,
+                "skip-menu" => launch.skip_menu = true,
+                "setup" => setup = Some(value.parse::<Setup>()?),
+                "moves" => {
+                    let turns: Result<Vec<Turn>, _> =
+                        value.split('-').filter(|t| !t.is_empty()).map(str::parse).collect();
+                    moves = Some(turns?)
+                }
+                _ => warn!("Skipping unknown query parameter {key:?}"),
+            }
+        }
+        let range = seeds_range(launch.pits.unwrap_or(6));
+        if let Some(seeds) = launch.seeds.filter(|s| !range.contains(s)) {
+            return Err(format!(
+                "invalid seeds {seeds}, expected {} to {}",
+                range.start(),
+                range.end()
+            ));
+        }
+        // a set up position is a game without moves so far
+        if setup.is_some() && moves.is_none() {
+            moves = Some(vec![]);
+        }
+        if let Some(setup) = &setup {
+            let rules = launch.rules.unwrap_or_default();
+            setup.check(launch.pits.unwrap_or(6), launch.seeds.unwrap_or(4), rules)?;
+        }
+        launch.load = moves.map(|moves| GameRecord {
+            pits: launch.pits.unwrap_or(6),
+            seeds: launch.seeds.unwrap_or(4),
+            rules: launch.rules.unwrap_or_default(),
+            setup,
+            moves,
+            result: None,
+        });
+        Ok(launch)
+    }
+    /// Setup from the page's URL, ignoring it when malformed
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_location() -> Self {
+        let query = web_sys::window().and_then(|w| w.location().search().ok());
+        match Self::from_query(&query.unwrap_or_default()) {
+            Ok(launch) => launch,
+            Err(e) => {
+                warn!("Ignoring the URL query: {e}");
+                Self::default()
+            }
+        }
+    }
+}
+
+/// URL query that `Launch::from_query` turns back into the game of `record`, against the same bot
+pub fn share_query(record: &GameRecord, cfg: &OwareCfg) -> String {
+    let mut query = format!(
+        "pits={}&seeds={}&rules={}",
+        record.pits, record.seeds, record.rules
+    );
+    match cfg.actors {
+        [Actor::Human, Actor::Bot(ai)] => query += &format!("&bot={ai}"),
+        [Actor::Bot(ai), Actor::Human] => query += &format!("&bot={ai}&human-second"),
+        _ => (),
+    }
+    if let Some(setup) = &record.setup {
+        query += &format!("&setup={}", setup.to_string().replace(' ', "+"));
+    }
+    let moves: Vec<_> = record.moves.iter().map(Turn::to_string).collect();
+    if moves.is_empty() && record.setup.is_none() {
+        query + "&skip-menu"
+    } else {
+        query + "&moves=" + &moves.join("-")
+    }
+}
+
+/// Undo the percent-encoding browsers may apply to query values
+fn decode(value: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = value.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) if b == b'%' => {
+                bytes.push(byte);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(if b == b'+' { b' ' } else { b });
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -1,20 +1,28 @@
 // mod actions;
-// mod audio;
+mod audio;
+mod launch;
 mod loading;
 mod menu;
 mod oware;
 // mod player;
+mod settings;
 mod tweens;
 
 use bevy::app::App;
 use bevy::prelude::*;
 
 // use actions::ActionsPlugin;
-// use audio::InternalAudioPlugin;
+use audio::InternalAudioPlugin;
 use loading::LoadingPlugin;
 use menu::MenuPlugin;
 use oware::OwarePlugin;
 // use player::PlayerPlugin;
+use settings::SettingsPlugin;
+
+pub use launch::Launch;
+pub use oware::{
+    seeds_range, Actor, Ai, Book, GameRecord, GrandSlam, OwareBoard, Rules, Tablebase, PITS,
+};
 
 #[cfg(debug_assertions)]
 use bevy::diagnostic::LogDiagnosticsPlugin;
@@ -30,6 +38,9 @@ enum GameState {
     Loading,
     Game,
     Menu,
+    Replay,
+    /// Setting up a position to play from
+    Editor,
 }
 
 pub struct GamePlugin;
@@ -39,19 +50,26 @@ impl Plugin for GamePlugin {
         app
             .add_state::<GameState>()
             .add_plugin(LoadingPlugin)
+            .add_plugin(SettingsPlugin)
             .add_plugin(MenuPlugin)
             .add_plugin(GameTweeningPlugin)
             // .add_plugin(ActionsPlugin)
-            // .add_plugin(InternalAudioPlugin)
+            .add_plugin(InternalAudioPlugin)
             // .add_plugin(PlayerPlugin);
             ;
 
-        #[cfg(not(feature = "dev"))]
-        app.add_plugin(OwarePlugin::<6>);
+        // one plugin per board size in `oware::PITS`, `OwareCfg::pits` picks the active one
+        app.add_plugin(OwarePlugin::<3>)
+            .add_plugin(OwarePlugin::<4>)
+            .add_plugin(OwarePlugin::<5>)
+            .add_plugin(OwarePlugin::<6>)
+            .add_plugin(OwarePlugin::<7>)
+            .add_plugin(OwarePlugin::<8>)
+            .add_plugin(OwarePlugin::<9>)
+            .add_plugin(OwarePlugin::<10>);
 
         #[cfg(feature = "dev")]
-        app.add_plugin(OwarePlugin::<4>)
-            .add_system(auto_start)
+        app.add_system(auto_start)
             .add_plugin(WorldInspectorPlugin::new());
 
         #[cfg(debug_assertions)]
@@ -80,6 +98,11 @@ fn auto_start(
     }
 }
 
+/// The board is on screen, for playing, a replay or editing
+fn on_board(state: Res<State<GameState>>) -> bool {
+    matches!(state.0, GameState::Game | GameState::Replay | GameState::Editor)
+}
+
 /// Despawn all entities with a given component type
 fn despawn_with<T: Component>(mut commands: Commands, q: Query<Entity, With<T>>) {
     for e in q.iter() {
@@ -1,8 +1,7 @@
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
-
-// use bevy_kira_audio::AudioSource;
+use bevy_kira_audio::AudioSource;
 
 pub struct LoadingPlugin;
 
@@ -12,7 +11,7 @@ impl Plugin for LoadingPlugin {
             LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu),
         )
         .add_collection_to_loading_state::<_, FontAssets>(GameState::Loading)
-        // .add_collection_to_loading_state::<_,AudioAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, AudioAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, BoardAssets>(GameState::Loading);
     }
 }
@@ -23,13 +22,15 @@ pub struct FontAssets {
     pub fira_sans: Handle<Font>,
 }
 
-/*
 #[derive(AssetCollection, Resource)]
 pub struct AudioAssets {
-    #[asset(path = "audio/flying.ogg")]
-    pub flying: Handle<AudioSource>,
+    #[asset(path = "audio/seed.wav")]
+    pub seed: Handle<AudioSource>,
+    #[asset(path = "audio/capture.wav")]
+    pub capture: Handle<AudioSource>,
+    #[asset(path = "audio/game_end.wav")]
+    pub game_end: Handle<AudioSource>,
 }
-*/
 
 pub fn sprite(texture: &Handle<Image>, size: f32, transform: Transform) -> SpriteBundle {
     SpriteBundle {
@@ -5,17 +5,99 @@ use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy::winit::WinitWindows;
 use bevy::DefaultPlugins;
-use oware::GamePlugin;
+use oware::{GamePlugin, Launch};
 use std::io::Cursor;
 use winit::window::Icon;
 
+const USAGE: &str = "\
+usage: oware [OPTIONS]
+
+options:
+    --pits N          pits per side
+    --seeds N         initial seeds per pit, at most 255 / (2 * pits)
+    --bot BOT         bot to play against, a level from beginner to master, or a spec
+                      like random, rollout:729, mcts:729:2 or minmax:6
+    --human-second    let the bot move first
+    --load FILE       continue a saved game
+    --window WxH      window size, default 600x800
+    --skip-menu       start a new game right away";
+
+struct Options {
+    launch: Launch,
+    window: (f32, f32),
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Self {
+            launch: Launch::default(),
+            window: (600., 800.),
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            let invalid = |v: &str| format!("invalid {arg} {v:?}");
+            match arg.as_str() {
+                "--pits" => {
+                    let v = value()?;
+                    let pits = v.parse().ok().filter(|p| oware::PITS.contains(p));
+                    opts.launch.pits = Some(pits.ok_or_else(|| invalid(&v))?)
+                }
+                "--seeds" => {
+                    let v = value()?;
+                    opts.launch.seeds = Some(v.parse().map_err(|_| invalid(&v))?)
+                }
+                "--bot" => opts.launch.bot = Some(value()?.parse()?),
+                "--human-second" => opts.launch.human_second = true,
+                "--load" => {
+                    let v = value()?;
+                    let text = std::fs::read_to_string(&v).map_err(|e| format!("{v}: {e}"))?;
+                    opts.launch.load = Some(text.parse().map_err(|e| format!("{v}: {e}"))?)
+                }
+                "--window" => {
+                    let v = value()?;
+                    // sizes winit can open a window with
+                    let size = |n: &str| n.parse().ok().filter(|s: &f32| s.is_finite() && *s > 0.);
+                    let parse = |(w, h): (&str, &str)| Some((size(w)?, size(h)?));
+                    opts.window = v.split_once('x').and_then(parse).ok_or_else(|| invalid(&v))?
+                }
+                "--skip-menu" => opts.launch.skip_menu = true,
+                "-h" | "--help" => return Err(String::new()),
+                _ => return Err(format!("unknown option {arg:?}")),
+            }
+        }
+        let range = oware::seeds_range(opts.launch.pits.unwrap_or(6));
+        if let Some(seeds) = opts.launch.seeds.filter(|s| !range.contains(s)) {
+            return Err(format!(
+                "invalid --seeds {seeds}, expected {} to {}",
+                range.start(),
+                range.end()
+            ));
+        }
+        Ok(opts)
+    }
+}
+
 fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2)
+        }
+    };
+    // the web build has no arguments, its setup comes from the page's URL
+    #[cfg(target_arch = "wasm32")]
+    let opts = Options {
+        launch: Launch::from_location(),
+        ..opts
+    };
     App::new()
         .insert_resource(Msaa::Sample2)
         .insert_resource(ClearColor(Color::rgb(0.4, 0.4, 0.4)))
+        .insert_resource(opts.launch)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
-                resolution: (600., 800.).into(),
+                resolution: opts.window.into(),
                 title: "Oware".to_string(),
                 canvas: Some("#bevy".to_owned()),
                 ..Default::default()
@@ -1,6 +1,9 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::oware::{GameRecord, LoadGame};
 use crate::{
     despawn_with,
-    oware::{Actor, Ai, PC},
+    launch::Launch,
+    oware::{Actor, Ai, Animation, Difficulty, Rules, TimeControl, PC, PITS, QUIET_LIMIT},
     GameState,
 };
 #[cfg(not(target_arch = "wasm32"))]
@@ -17,6 +20,17 @@ enum Screens {
     NewGame,
     GameOver,
     Seeds,
+    Pits,
+    Rules,
+    Clock,
+    Settings,
+    Actor(usize),
+    /// Bots by their raw parameters, for the player with this index
+    Advanced(usize),
+    Custom(usize),
+    Hint,
+    Replay,
+    Editor,
 }
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum Actions {
@@ -24,26 +38,104 @@ pub enum Actions {
     Pause,
     #[cfg(not(target_arch = "wasm32"))]
     Quit,
+    #[cfg(not(target_arch = "wasm32"))]
+    Save,
+    #[cfg(not(target_arch = "wasm32"))]
+    Load,
+    #[cfg(target_arch = "wasm32")]
+    ShareLink,
     NewGame,
-    PlayerAsFirst,
-    Bot(Ai),
+    SwapActors,
+    SetActor(usize, Actor),
     SetSeeds(u8),
+    SetPits(usize),
+    SetRules(Rules),
+    SetClock(TimeControl),
+    SetAnimation(Animation),
+    SetBotDelay(u32),
+    SetVolume(u8),
+    ToggleMute,
+    SetHint(Ai),
+    /// Step a parameter of the custom bot up or down
+    Tune(Tune, bool),
+    Hint,
+    ToggleMoves,
+    Undo,
+    Redo,
+    Replay,
+    ReplayToggle,
+    StepBack,
+    StepForward,
+    Slower,
+    Faster,
+    ExitReplay,
+    Edit,
+    SwitchSides,
+    ClearBoard,
+    PlayFromHere,
 }
 impl ActionTrait for Actions {
     type State = OwareCfg;
     type Event = Self;
     fn handle(&self, state: &mut Self::State, event_writer: &mut EventWriter<Self::Event>) {
         match self {
-            Self::Pause | Self::Resume => event_writer.send(*self),
+            Self::Pause
+            | Self::Resume
+            | Self::Hint
+            | Self::Replay
+            | Self::ReplayToggle
+            | Self::StepBack
+            | Self::StepForward
+            | Self::Slower
+            | Self::Faster
+            | Self::ExitReplay
+            | Self::SwitchSides
+            | Self::ClearBoard => event_writer.send(*self),
+            Self::Edit => {
+                state.pits = state.new_pits;
+                state.clear_outcome();
+                event_writer.send(*self)
+            }
+            Self::PlayFromHere => {
+                state.clear_outcome();
+                event_writer.send(*self)
+            }
             #[cfg(not(target_arch = "wasm32"))]
-            Self::Quit => event_writer.send(*self),
+            Self::Quit | Self::Save | Self::Load => event_writer.send(*self),
+            #[cfg(target_arch = "wasm32")]
+            Self::ShareLink => event_writer.send(*self),
             Self::NewGame => {
                 state.new_game = true;
+                state.pits = state.new_pits;
                 event_writer.send(*self)
             }
-            Self::PlayerAsFirst => state.human_is_first ^= true,
-            Self::Bot(ai) => state.ai = *ai,
+            Self::SwapActors => state.actors.swap(0, 1),
+            Self::SetActor(i, actor) => state.actors[*i] = *actor,
             Self::SetSeeds(n) => state.init_seeds = *n,
+            Self::SetPits(n) => state.new_pits = *n,
+            Self::SetRules(rules) => state.rules = *rules,
+            Self::SetClock(clock) => state.time_control = *clock,
+            Self::SetAnimation(animation) => state.animation = *animation,
+            Self::SetBotDelay(millis) => state.bot_delay = *millis,
+            Self::SetVolume(volume) => state.volume = *volume,
+            Self::ToggleMute => state.muted ^= true,
+            Self::SetHint(ai) => state.hint = *ai,
+            Self::Tune(param, up) => {
+                state.custom.tune(*param, *up);
+                // players on the custom bot follow its parameters
+                for actor in &mut state.actors {
+                    *actor = match *actor {
+                        Actor::Bot(Ai::CustomRollout(_)) => Actor::Bot(state.custom.rollout()),
+                        Actor::Bot(Ai::CustomMcts(..)) => Actor::Bot(state.custom.mcts()),
+                        other => other,
+                    };
+                }
+            }
+            Self::ToggleMoves => state.show_moves ^= true,
+            Self::Undo | Self::Redo => {
+                state.clear_outcome();
+                event_writer.send(*self)
+            }
         }
     }
 }
@@ -56,56 +148,258 @@ impl ScreenTrait for Screens {
     ) -> bevy_quickmenu::Menu<Self> {
         let seed_actions =
             |n| MenuItem::action(format!("{n}"), Actions::SetSeeds(n)).checked(state.init_seeds == n);
-        let bot_list = &mut [
-            Ai::Random,
-            Ai::Rollout(27),
-            Ai::Rollout(729),
-            Ai::Mcts(27, 1),
-            Ai::Mcts(729, 2),
-        ]
-        .iter()
-        .map(|&x| MenuItem::action(format!("{x:?}"), Actions::Bot(x)).checked(state.ai == x))
-        .collect::<Vec<MenuItem<Screens>>>();
+        let pit_actions =
+            |n| MenuItem::action(format!("{n}"), Actions::SetPits(n)).checked(state.new_pits == n);
+        let actor_action = |i: usize, x: Actor| {
+            MenuItem::action(x.name(), Actions::SetActor(i, x)).checked(state.actors[i] == x)
+        };
         Menu::new(
             format!("{self:?}"),
             match self {
                 Self::Pause => vec![
                     MenuItem::headline("Paused"),
                     MenuItem::action("Resume", Actions::Resume),
+                    MenuItem::action("Hint (H)", Actions::Hint),
+                    MenuItem::screen(
+                        format!("Hint Strength: {}", state.hint.name()),
+                        Screens::Hint,
+                    ),
+                    MenuItem::action("Undo", Actions::Undo),
+                    MenuItem::action("Redo", Actions::Redo),
+                    MenuItem::action("Replay", Actions::Replay),
+                    MenuItem::action("Move List", Actions::ToggleMoves).checked(state.show_moves),
+                    MenuItem::screen("Settings", Screens::Settings),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    MenuItem::action("Save Game", Actions::Save),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    MenuItem::action("Load Game", Actions::Load),
+                    #[cfg(target_arch = "wasm32")]
+                    MenuItem::action("Copy Share Link", Actions::ShareLink),
                     MenuItem::screen("New Game", Screens::NewGame),
                     #[cfg(not(target_arch = "wasm32"))]
                     MenuItem::action("Quit", Actions::Quit),
                 ],
-                Self::Game => vec![MenuItem::action("Pause", Actions::Pause)],
+                Self::Game => vec![
+                    MenuItem::action("Pause", Actions::Pause),
+                    MenuItem::action("Hint", Actions::Hint),
+                ],
+                Self::Replay => vec![
+                    MenuItem::headline("Replay"),
+                    MenuItem::action("Play / Pause (P)", Actions::ReplayToggle),
+                    MenuItem::action("Step Back (,)", Actions::StepBack),
+                    MenuItem::action("Step Forward (.)", Actions::StepForward),
+                    MenuItem::action("Slower (-)", Actions::Slower),
+                    MenuItem::action("Faster (=)", Actions::Faster),
+                    MenuItem::action("Exit Replay", Actions::ExitReplay),
+                ],
+                Self::Editor => vec![
+                    MenuItem::headline("Position Editor"),
+                    MenuItem::label("Click a bowl to add a seed, right click to take one"),
+                    MenuItem::action("Switch Side to Move (Tab)", Actions::SwitchSides),
+                    MenuItem::action("Clear Board", Actions::ClearBoard),
+                    MenuItem::action("Play from Here (Enter)", Actions::PlayFromHere),
+                    MenuItem::screen("Back", Screens::NewGame),
+                ],
                 Self::GameOver => vec![
                     MenuItem::headline(state.outcome()),
+                    MenuItem::action("Undo", Actions::Undo),
+                    MenuItem::action("Replay", Actions::Replay),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    MenuItem::action("Save Game", Actions::Save),
+                    #[cfg(target_arch = "wasm32")]
+                    MenuItem::action("Copy Share Link", Actions::ShareLink),
                     MenuItem::screen("New Game", Screens::NewGame),
                     #[cfg(not(target_arch = "wasm32"))]
                     MenuItem::action("Quit", Actions::Quit),
                 ],
-                Self::NewGame => {
-                    let mut items = vec![
-                        MenuItem::headline("Oware"),
-                        MenuItem::action("Start a New Game", Actions::NewGame),
-                        MenuItem::label("Configuration"),
-                        MenuItem::label("Player Position"),
-                        MenuItem::action("Is First", Actions::PlayerAsFirst)
-                            .checked(state.human_is_first),
-                        MenuItem::screen("Initial Seeds", Screens::Seeds),
-                        MenuItem::label("Bot Type"),
-                    ];
-                    items.append(bot_list);
-                    items
-                },
+                Self::NewGame => vec![
+                    MenuItem::headline("Oware"),
+                    MenuItem::action("Start a New Game", Actions::NewGame),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    MenuItem::action("Load Game", Actions::Load),
+                    MenuItem::action("Set Up a Position", Actions::Edit),
+                    MenuItem::label("Configuration"),
+                    MenuItem::label("Players"),
+                    MenuItem::screen(
+                        format!("First: {}", state.actors[0].name()),
+                        Screens::Actor(0),
+                    ),
+                    MenuItem::screen(
+                        format!("Second: {}", state.actors[1].name()),
+                        Screens::Actor(1),
+                    ),
+                    MenuItem::action("Swap Players", Actions::SwapActors),
+                    MenuItem::screen(format!("Rules: {}", state.rules.name()), Screens::Rules),
+                    MenuItem::screen(
+                        format!("Clock: {}", state.time_control.name()),
+                        Screens::Clock,
+                    ),
+                    MenuItem::screen("Pits per Side", Screens::Pits),
+                    MenuItem::screen("Initial Seeds", Screens::Seeds),
+                    MenuItem::screen("Settings", Screens::Settings),
+                ],
                 Self::Seeds => [MenuItem::headline("Initial Seeds")]
                     .into_iter()
                     .chain((3..6).map(|x| seed_actions(x)))
                     .collect(),
+                Self::Rules => [MenuItem::headline("Rules")]
+                    .into_iter()
+                    .chain(Rules::ALL.map(|r| {
+                        MenuItem::action(r.name(), Actions::SetRules(r)).checked(state.rules == r)
+                    }))
+                    .chain([MenuItem::label(format!(
+                        "House rule: {QUIET_LIMIT} moves without a capture end the game"
+                    ))])
+                    .collect(),
+                Self::Clock => [MenuItem::headline("Clock")]
+                    .into_iter()
+                    .chain(TimeControl::ALL.map(|c| {
+                        MenuItem::action(c.name(), Actions::SetClock(c))
+                            .checked(state.time_control == c)
+                    }))
+                    .collect(),
+                Self::Settings => [MenuItem::headline("Settings"), MenuItem::label("Animation")]
+                    .into_iter()
+                    .chain(Animation::ALL.map(|a| {
+                        MenuItem::action(format!("{a:?}"), Actions::SetAnimation(a))
+                            .checked(state.animation == a)
+                    }))
+                    .chain([MenuItem::label("Bot Delay")])
+                    .chain(BOT_DELAYS.map(|ms| {
+                        let label = format!("{:.2} s", ms as f32 / 1000.);
+                        MenuItem::action(label, Actions::SetBotDelay(ms))
+                            .checked(state.bot_delay == ms)
+                    }))
+                    .chain([
+                        MenuItem::label("Sound"),
+                        MenuItem::action("Mute", Actions::ToggleMute).checked(state.muted),
+                    ])
+                    .chain([25, 50, 75, 100].map(|v| {
+                        MenuItem::action(format!("Volume {v}%"), Actions::SetVolume(v))
+                            .checked(state.volume == v)
+                    }))
+                    .collect(),
+                Self::Pits => [MenuItem::headline("Pits per Side")]
+                    .into_iter()
+                    .chain(PITS.map(pit_actions))
+                    .collect(),
+                Self::Actor(i) => [MenuItem::headline(if *i == 0 { "First" } else { "Second" })]
+                    .into_iter()
+                    .chain([actor_action(*i, Actor::Human)])
+                    .chain(Difficulty::ALL.map(|d| actor_action(*i, Actor::Bot(Ai::Level(d)))))
+                    .chain([MenuItem::screen("Advanced", Screens::Advanced(*i))])
+                    .collect(),
+                Self::Advanced(i) => [MenuItem::headline("Advanced")]
+                    .into_iter()
+                    .chain(BOTS.map(|ai| actor_action(*i, Actor::Bot(ai))))
+                    .chain([MenuItem::screen("Custom Bot", Screens::Custom(*i))])
+                    .collect(),
+                Self::Custom(i) => {
+                    let custom = state.custom;
+                    let tune = |name: &str, param| {
+                        [
+                            MenuItem::action(format!("{name} +"), Actions::Tune(param, true)),
+                            MenuItem::action(format!("{name} -"), Actions::Tune(param, false)),
+                        ]
+                    };
+                    [
+                        MenuItem::headline("Custom Bot"),
+                        MenuItem::label(format!("Rollouts: {}", custom.rollouts)),
+                    ]
+                    .into_iter()
+                    .chain(tune("Rollouts", Tune::Rollouts))
+                    .chain([
+                        actor_action(*i, Actor::Bot(custom.rollout())),
+                        MenuItem::label(format!("MCTS Iterations: {}", custom.iterations)),
+                    ])
+                    .chain(tune("Iterations", Tune::Iterations))
+                    .chain([MenuItem::label(format!(
+                        "Exploration Weight: {}",
+                        custom.exploration
+                    ))])
+                    .chain(tune("Exploration Weight", Tune::Exploration))
+                    .chain([actor_action(*i, Actor::Bot(custom.mcts()))])
+                    .collect()
+                }
+                Self::Hint => [MenuItem::headline("Hint Strength")]
+                    .into_iter()
+                    .chain(BOTS.map(|ai| {
+                        MenuItem::action(ai.name(), Actions::SetHint(ai)).checked(state.hint == ai)
+                    }))
+                    .collect(),
             },
         )
     }
 }
 
+/// Bots offered by their parameters, under "Advanced" for players and for hints
+const BOTS: [Ai; 9] = [
+    Ai::Random,
+    Ai::Rollout(27),
+    Ai::Rollout(729),
+    Ai::Mcts(27, 1),
+    Ai::Mcts(729, 2),
+    Ai::BookMcts(729, 2),
+    Ai::MinMax(4),
+    Ai::MinMax(8),
+    Ai::Endgame(8),
+];
+
+/// Parameter of the custom bot
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Tune {
+    Rollouts,
+    Iterations,
+    Exploration,
+}
+
+/// Rollout and MCTS bot parameters picked on the "Custom Bot" screen
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CustomBot {
+    pub rollouts: u32,
+    pub iterations: u32,
+    pub exploration: u8,
+}
+impl Default for CustomBot {
+    fn default() -> Self {
+        Self {
+            rollouts: 729,
+            iterations: 729,
+            exploration: 2,
+        }
+    }
+}
+impl CustomBot {
+    /// Most rollouts or iterations, as the bot's budget grows with them
+    const MAX_COUNT: u32 = 6561;
+
+    pub fn rollout(&self) -> Ai {
+        Ai::CustomRollout(self.rollouts)
+    }
+    pub fn mcts(&self) -> Ai {
+        Ai::CustomMcts(self.iterations, self.exploration)
+    }
+    /// Counts step by a factor of 3 up to 3^8, the exploration weight by 1 up to 9
+    fn tune(&mut self, param: Tune, up: bool) {
+        let count = |n: u32| {
+            if up {
+                (n * 3).min(Self::MAX_COUNT)
+            } else {
+                (n / 3).max(1)
+            }
+        };
+        match param {
+            Tune::Rollouts => self.rollouts = count(self.rollouts),
+            Tune::Iterations => self.iterations = count(self.iterations),
+            Tune::Exploration if up => self.exploration = (self.exploration + 1).min(9),
+            Tune::Exploration => self.exploration = self.exploration.saturating_sub(1),
+        }
+    }
+}
+
+/// Pauses before a bot moves offered in the settings, in milliseconds
+const BOT_DELAYS: [u32; 5] = [0, 250, 729, 1729, 3000];
+
 // TODO move to oware
 fn cleanup(cfg: Option<Res<OwareCfg>>) -> bool {
     cfg.map_or(false, |cfg| cfg.new_game || cfg.outcome.is_some())
@@ -113,48 +407,90 @@ fn cleanup(cfg: Option<Res<OwareCfg>>) -> bool {
 
 #[derive(Resource, Clone, Copy)]
 pub struct OwareCfg {
-    pub human_is_first: bool,
-    pub ai: Ai,
+    /// Who plays for `Player::A` and `Player::B`
+    pub actors: [Actor; 2],
     pub new_game: bool,
     pub outcome: Option<Outcome>,
     pub init_seeds: u8,
+    /// Pits per side of the board in play, picks which `OwarePlugin` runs
+    pub pits: usize,
+    /// Pits per side for new games, taking over `pits` when one starts
+    pub new_pits: usize,
+    /// Rules for new games, a loaded game keeps its own
+    pub rules: Rules,
+    /// Bot that searches the move suggested by a hint
+    pub hint: Ai,
+    pub custom: CustomBot,
+    /// Show the move list panel next to the board
+    pub show_moves: bool,
+    /// Clocks for new games
+    pub time_control: TimeControl,
+    /// The outcome was decided by a clock running out
+    pub on_time: bool,
+    pub animation: Animation,
+    /// Pause before a bot starts thinking, in milliseconds
+    pub bot_delay: u32,
+    /// Master volume in percent
+    pub volume: u8,
+    pub muted: bool,
 }
 impl Default for OwareCfg {
     fn default() -> Self {
         Self {
-            human_is_first: true,
-            ai: Ai::Random,
+            actors: [Actor::Human, Actor::Bot(Ai::Level(Difficulty::Novice))],
             outcome: None,
             new_game: false,
             init_seeds: if cfg!(feature = "dev") { 2 } else { 4 },
+            pits: if cfg!(feature = "dev") { 4 } else { 6 },
+            new_pits: if cfg!(feature = "dev") { 4 } else { 6 },
+            rules: Rules::default(),
+            hint: Ai::MinMax(8),
+            custom: CustomBot::default(),
+            show_moves: false,
+            time_control: TimeControl::default(),
+            on_time: false,
+            animation: Animation::default(),
+            bot_delay: 1729,
+            volume: 75,
+            muted: false,
         }
     }
 }
 impl OwareCfg {
     pub fn get_actor(&self, player: Player) -> Actor {
-        if player.index() != self.human_is_first as u8 {
-            Actor::Human
-        } else {
-            Actor::Bot(self.ai)
-        }
+        self.actors[player.index() as usize]
     }
     pub fn is_human(&self, player: Player) -> bool {
-        player.index() != self.human_is_first as u8
+        self.get_actor(player).is_human()
+    }
+    /// Forget how the last game ended, as play goes on from another position
+    pub fn clear_outcome(&mut self) {
+        self.outcome = None;
+        self.on_time = false;
     }
     pub fn outcome(&self) -> String {
-        format!(
-            "{:?}",
-            self.outcome.pov(if self.human_is_first {
-                Player::A
-            } else {
-                Player::B
-            })
-        )
+        let humans: Vec<_> = Player::BOTH.into_iter().filter(|&p| self.is_human(p)).collect();
+        let outcome = match (humans.as_slice(), self.outcome) {
+            ([human], outcome) => format!("{:?}", outcome.pov(*human)),
+            (_, Some(Outcome::WonBy(p))) => format!("{p:?} ({}) Won", self.get_actor(p).name()),
+            (_, outcome) => format!("{outcome:?}"),
+        };
+        if self.on_time {
+            format!("{outcome} on Time")
+        } else {
+            outcome
+        }
     }
 }
 
-fn menu(mut commands: Commands, cfg: Option<Res<OwareCfg>>, state: Res<State<GameState>>) {
-    let in_game = state.0 == GameState::Game;
+fn menu(
+    mut commands: Commands,
+    cfg: Option<Res<OwareCfg>>,
+    state: Res<State<GameState>>,
+    launch: Option<Res<Launch>>,
+    mut started: Local<bool>,
+) {
+    let in_game = matches!(state.0, GameState::Game | GameState::Replay | GameState::Editor);
     let sheet = Stylesheet::default()
         .with_background(BackgroundColor(Color::BLACK))
         .with_style(Style {
@@ -166,14 +502,23 @@ fn menu(mut commands: Commands, cfg: Option<Res<OwareCfg>>, state: Res<State<Gam
             ..default()
         });
 
-    let new_game = cfg.is_none();
+    // settings are loaded before the first menu, no game was played yet
+    let new_game = !*started;
+    *started = true;
+    if new_game && launch.map_or(false, |l| l.skips_menu()) {
+        commands.insert_resource(NextState(Some(GameState::Game)));
+    }
     if cfg.is_none() {
         commands.insert_resource(OwareCfg::default());
     }
     let cfg = cfg.map_or(OwareCfg::default(), |x| x.clone());
     commands.insert_resource(MenuState::new(
         cfg,
-        if cfg.outcome.is_some() {
+        if state.0 == GameState::Replay {
+            Screens::Replay
+        } else if state.0 == GameState::Editor {
+            Screens::Editor
+        } else if cfg.outcome.is_some() {
             Screens::GameOver
         } else if new_game {
             Screens::NewGame
@@ -190,18 +535,40 @@ fn handle_events(
     #[cfg(not(target_arch = "wasm32"))] mut app_event: EventWriter<AppExit>,
     mut commands: Commands,
     menu_state: Option<Res<MenuState<Screens>>>,
+    state: Res<State<GameState>>,
 ) {
-    if let Some(menu_state) = menu_state {
+    if let Some(menu_state) = &menu_state {
         if !action_event.is_empty() {
             commands.insert_resource(*menu_state.state());
         }
     }
     for event in action_event.iter() {
         match event {
-            Actions::Resume | Actions::NewGame => {
+            Actions::Resume | Actions::NewGame | Actions::Undo | Actions::Redo => {
+                commands.insert_resource(NextState(Some(GameState::Game)))
+            }
+            // re-entering `Game` would rebuild the board and drop the search
+            Actions::Hint if state.0 == GameState::Menu => {
                 commands.insert_resource(NextState(Some(GameState::Game)))
             }
-            Actions::Pause => commands.insert_resource(NextState(Some(GameState::Menu))),
+            Actions::Pause | Actions::ExitReplay => {
+                commands.insert_resource(NextState(Some(GameState::Menu)))
+            }
+            Actions::Replay => commands.insert_resource(NextState(Some(GameState::Replay))),
+            Actions::Edit => commands.insert_resource(NextState(Some(GameState::Editor))),
+            // the save file is read once here, the plugin for its board size plays it out
+            #[cfg(not(target_arch = "wasm32"))]
+            Actions::Load => match GameRecord::read() {
+                Ok(record) => {
+                    let mut cfg = menu_state.as_ref().map_or_else(default, |m| *m.state());
+                    cfg.pits = record.pits;
+                    cfg.clear_outcome();
+                    commands.insert_resource(cfg);
+                    commands.insert_resource(LoadGame(record));
+                    commands.insert_resource(NextState(Some(GameState::Game)));
+                }
+                Err(e) => error!("Could not load game: {e}"),
+            },
             #[cfg(not(target_arch = "wasm32"))]
             Actions::Quit => app_event.send(AppExit),
             _ => (),
@@ -0,0 +1,476 @@
+//! Mancala rules behind `Oware<P>`
+//!
+//! Bowls are numbered the way the board is drawn: pits `0..P` belong to `Player::A`,
+//! pits `P..2 * P` to `Player::B`, and `2 * P + i` is the store of the player with
+//! index `i`. Seeds are sown in increasing pit order, which runs counter-clockwise
+//! around the board.
+use std::fmt::{self, Display};
+use std::ops::Range;
+use std::str::FromStr;
+
+use board_game::{
+    board::{Board, BoardMoves, BoardSymmetry, BruteforceMoveIterator, Outcome, Player},
+    symmetry::UnitSymmetry,
+    util::iter::ClonableInternal,
+};
+
+/// Plies without a capture after which the game is called and each side keeps its seeds
+///
+/// A house rule of this game in every variant, in place of the agreement to stop that
+/// ends endless games over a real board. The Rules menu shows it.
+pub const QUIET_LIMIT: u8 = 100;
+
+/// What happens to a move that would capture every seed of the opponent
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum GrandSlam {
+    /// The move is played but captures nothing
+    #[default]
+    NoCapture,
+    /// The move may not be played while any other move is possible
+    Forbidden,
+    /// The seeds are captured, but go to the opponent's store
+    ToOpponent,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Rules {
+    /// Oware, sowing skips the stores and captures pits of 2 or 3 on the opponent's side
+    Oware(GrandSlam),
+    /// Sowing passes through the own store, where the last seed earns another turn,
+    /// and the last seed landing in an own empty pit captures the opposite pit
+    Kalah,
+}
+impl Default for Rules {
+    fn default() -> Self {
+        Self::Oware(GrandSlam::default())
+    }
+}
+impl Rules {
+    pub const ALL: [Self; 4] = [
+        Self::Oware(GrandSlam::NoCapture),
+        Self::Oware(GrandSlam::Forbidden),
+        Self::Oware(GrandSlam::ToOpponent),
+        Self::Kalah,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Oware(GrandSlam::NoCapture) => "Abapa",
+            Self::Oware(GrandSlam::Forbidden) => "Oware, no Grand Slam",
+            Self::Oware(GrandSlam::ToOpponent) => "Oware, Grand Slam to Opponent",
+            Self::Kalah => "Kalah",
+        }
+    }
+}
+/// Names as used in game records
+impl Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Oware(GrandSlam::NoCapture) => "Abapa",
+            Self::Oware(GrandSlam::Forbidden) => "GrandSlamForbidden",
+            Self::Oware(GrandSlam::ToOpponent) => "GrandSlamToOpponent",
+            Self::Kalah => "Kalah",
+        })
+    }
+}
+impl FromStr for Rules {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|r| r.to_string() == s)
+            .ok_or_else(|| format!("unknown rules {s:?}"))
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct OwareBoard<const P: usize> {
+    rules: Rules,
+    init_seeds: u8,
+    pits: [[u8; P]; 2],
+    scores: [u8; 2],
+    next_player: Player,
+    quiet_plies: u8,
+    outcome: Option<Outcome>,
+}
+
+impl<const P: usize> Default for OwareBoard<P> {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl<const P: usize> OwareBoard<P> {
+    pub fn new(init_seeds: u8) -> Self {
+        Self::with_rules(init_seeds, Rules::default())
+    }
+    pub fn with_rules(init_seeds: u8, rules: Rules) -> Self {
+        Self {
+            rules,
+            init_seeds,
+            pits: [[init_seeds; P]; 2],
+            scores: [0; 2],
+            next_player: Player::A,
+            quiet_plies: 0,
+            outcome: None,
+        }
+    }
+    /// Position with `pits` on the board, empty stores and `next_player` to move
+    pub fn with_pits(rules: Rules, pits: [[u8; P]; 2], next_player: Player) -> Self {
+        Self {
+            pits,
+            next_player,
+            ..Self::with_rules(0, rules)
+        }
+    }
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+    pub fn init_seeds(&self) -> u8 {
+        self.init_seeds
+    }
+    pub fn get_seeds(&self, player: Player, pit: usize) -> u8 {
+        self.pits[player.index() as usize][pit]
+    }
+    pub fn score(&self, player: Player) -> u8 {
+        self.scores[player.index() as usize]
+    }
+    /// Seeds in any bowl, pit or store
+    pub fn seeds_at(&self, bowl: usize) -> u8 {
+        if bowl < 2 * P {
+            self.pits[bowl / P][bowl % P]
+        } else {
+            self.scores[bowl - 2 * P]
+        }
+    }
+    /// Put `seeds` in any bowl, for setting up positions
+    pub fn set_seeds(&mut self, bowl: usize, seeds: u8) {
+        *self.seeds_at_mut(bowl) = seeds;
+    }
+    pub fn set_next_player(&mut self, player: Player) {
+        self.next_player = player;
+    }
+    fn seeds_at_mut(&mut self, bowl: usize) -> &mut u8 {
+        if bowl < 2 * P {
+            &mut self.pits[bowl / P][bowl % P]
+        } else {
+            &mut self.scores[bowl - 2 * P]
+        }
+    }
+    fn side(&self, player: Player) -> u8 {
+        self.pits[player.index() as usize].iter().sum()
+    }
+    /// Bowls the player to move sows into, in order, starting at the first pit of `A`
+    fn ring(&self) -> Vec<usize> {
+        let me = self.next_player.index() as usize;
+        (0..2)
+            .flat_map(|side| {
+                let store = (self.rules == Rules::Kalah && side == me).then_some(2 * P + side);
+                (side * P..(side + 1) * P).chain(store)
+            })
+            .collect()
+    }
+    /// Bowl each seed of pit `mv` of the player to move lands in, in sowing order
+    pub fn path(&self, mv: usize) -> Vec<usize> {
+        let ring = self.ring();
+        let origin = self.next_player.index() as usize * P + mv;
+        let start = ring.iter().position(|&b| b == origin).unwrap();
+        // oware sowing skips the emptied pit on laps around the board
+        let skip = matches!(self.rules, Rules::Oware(_)).then_some(origin);
+        ring.iter()
+            .cycle()
+            .skip(start + 1)
+            .filter(|&&b| Some(b) != skip)
+            .take(self.pits[origin / P][mv] as usize)
+            .copied()
+            .collect()
+    }
+    /// Board after sowing and capturing, before the end of game checks,
+    /// and whether the move was a grand slam
+    fn sown(&self, mv: usize) -> (Self, bool) {
+        let me = self.next_player.index() as usize;
+        let path = self.path(mv);
+        let mut board = self.clone();
+        board.pits[me][mv] = 0;
+        for &b in &path {
+            *board.seeds_at_mut(b) += 1;
+        }
+        let last = *path.last().expect("sowing an empty pit");
+        let mut captured = 0;
+        let mut grand_slam = false;
+        match self.rules {
+            Rules::Oware(rule) => {
+                let mut victims = vec![];
+                let mut b = last;
+                while b / P == 1 - me && matches!(board.seeds_at(b), 2 | 3) {
+                    victims.push(b);
+                    if b % P == 0 {
+                        break;
+                    }
+                    b -= 1;
+                }
+                let taken: u8 = victims.iter().map(|&b| board.seeds_at(b)).sum();
+                grand_slam = taken > 0 && taken == board.side(self.next_player.other());
+                let store = match rule {
+                    _ if !grand_slam => Some(me),
+                    GrandSlam::ToOpponent => Some(1 - me),
+                    GrandSlam::NoCapture | GrandSlam::Forbidden => None,
+                };
+                if let Some(store) = store {
+                    victims.iter().for_each(|&b| *board.seeds_at_mut(b) = 0);
+                    board.scores[store] += taken;
+                    captured = taken;
+                }
+                board.next_player = self.next_player.other();
+            }
+            Rules::Kalah => {
+                let opposite = (1 - me) * P + P - 1 - last % P;
+                if last / P == me && board.seeds_at(last) == 1 && board.seeds_at(opposite) > 0 {
+                    captured = 1 + board.seeds_at(opposite);
+                    *board.seeds_at_mut(last) = 0;
+                    *board.seeds_at_mut(opposite) = 0;
+                    board.scores[me] += captured;
+                }
+                // the last seed in the own store earns another turn
+                if last != 2 * P + me {
+                    board.next_player = self.next_player.other();
+                }
+            }
+        }
+        board.quiet_plies = if captured > 0 {
+            0
+        } else {
+            board.quiet_plies.saturating_add(1)
+        };
+        (board, grand_slam)
+    }
+    /// Board after sowing and capturing `mv`, leaving the end of game checks of `play` out
+    pub fn sow(&self, mv: usize) -> Self {
+        self.sown(mv).0
+    }
+    /// The move leaves the opponent something to play
+    fn feeds(&self, mv: usize) -> bool {
+        let (board, _) = self.sown(mv);
+        let them = self.next_player.other();
+        // a grand slam handed to the opponent feeds them through their store
+        board.side(them) > 0
+            || (self.rules == Rules::Oware(GrandSlam::ToOpponent)
+                && board.score(them) > self.score(them))
+    }
+    fn allowed(&self, mv: usize) -> bool {
+        self.feeds(mv)
+            && !(self.rules == Rules::Oware(GrandSlam::Forbidden) && self.sown(mv).1)
+    }
+    /// Ends the game on a majority of seeds, or when the player to move is stuck,
+    /// in which case every player keeps the seeds on their own side
+    fn check_end(&mut self) {
+        let total: u32 = self.scores.iter().map(|&s| s as u32).sum::<u32>()
+            + Player::BOTH.iter().map(|&p| self.side(p) as u32).sum::<u32>();
+        if let Some(p) = Player::BOTH
+            .into_iter()
+            .find(|&p| 2 * self.score(p) as u32 > total)
+        {
+            self.outcome = Some(Outcome::WonBy(p));
+            return;
+        }
+        let stuck = match self.rules {
+            Rules::Oware(_) => !(0..P).any(|mv| self.is_available_move(mv)),
+            Rules::Kalah => Player::BOTH.iter().any(|&p| self.side(p) == 0),
+        };
+        if stuck || self.quiet_plies >= QUIET_LIMIT {
+            for p in 0..2 {
+                self.scores[p] += self.pits[p].iter().sum::<u8>();
+                self.pits[p] = [0; P];
+            }
+            let [a, b] = self.scores;
+            self.outcome = Some(match a.cmp(&b) {
+                std::cmp::Ordering::Greater => Outcome::WonBy(Player::A),
+                std::cmp::Ordering::Less => Outcome::WonBy(Player::B),
+                std::cmp::Ordering::Equal => Outcome::Draw,
+            });
+        }
+    }
+}
+
+impl<const P: usize> Board for OwareBoard<P> {
+    type Move = usize;
+
+    fn next_player(&self) -> Player {
+        self.next_player
+    }
+    fn is_available_move(&self, mv: usize) -> bool {
+        if self.outcome.is_some() || mv >= P || self.get_seeds(self.next_player, mv) == 0 {
+            return false;
+        }
+        match self.rules {
+            Rules::Kalah => true,
+            // fall back to any feeding move when only forbidden ones are left
+            Rules::Oware(_) => {
+                let playable = |m| self.get_seeds(self.next_player, m) > 0;
+                self.allowed(mv)
+                    || (self.feeds(mv) && !(0..P).any(|m| playable(m) && self.allowed(m)))
+            }
+        }
+    }
+    fn play(&mut self, mv: usize) {
+        assert!(self.is_available_move(mv), "{mv} is not available on\n{self}");
+        *self = self.sown(mv).0;
+        self.check_end();
+    }
+    fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+    fn can_lose_after_move() -> bool {
+        true
+    }
+}
+
+impl<const P: usize> BoardSymmetry<OwareBoard<P>> for OwareBoard<P> {
+    type Symmetry = UnitSymmetry;
+
+    fn map(&self, _: UnitSymmetry) -> Self {
+        self.clone()
+    }
+    fn map_move(&self, _: UnitSymmetry, mv: usize) -> usize {
+        mv
+    }
+}
+
+impl<'a, const P: usize> BoardMoves<'a, OwareBoard<P>> for OwareBoard<P> {
+    type AllMovesIterator = ClonableInternal<Range<usize>>;
+    type AvailableMovesIterator = BruteforceMoveIterator<'a, OwareBoard<P>>;
+
+    fn all_possible_moves() -> Self::AllMovesIterator {
+        ClonableInternal::new(0..P)
+    }
+    fn available_moves(&'a self) -> Self::AvailableMovesIterator {
+        BruteforceMoveIterator::new(self)
+    }
+}
+
+/// `B`'s pits right to left on top, `A`'s pits left to right below, stores on the side
+impl<const P: usize> Display for OwareBoard<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |p: usize| {
+            let mut pits = self.pits[p].map(|s| format!("{s:>3}"));
+            if p == 1 {
+                pits.reverse();
+            }
+            pits.concat()
+        };
+        writeln!(f, "{:>3} |{}", self.scores[1], row(1))?;
+        writeln!(f, "    |{}| {:>3}", row(0), self.scores[0])?;
+        write!(f, "{:?} to move, {}", self.next_player, self.rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(rules: Rules, a: [u8; 6], b: [u8; 6], next_player: Player) -> OwareBoard<6> {
+        OwareBoard::with_pits(rules, [a, b], next_player)
+    }
+    fn played(mut board: OwareBoard<6>, mv: usize) -> OwareBoard<6> {
+        board.play(mv);
+        board
+    }
+
+    #[test]
+    fn sowing_skips_the_emptied_pit() {
+        let board = board(Rules::default(), [12, 0, 0, 0, 0, 0], [1; 6], Player::A);
+        let path: Vec<_> = (1..12).chain([1]).collect();
+        assert_eq!(board.path(0), path);
+        let after = played(board, 0);
+        assert_eq!(after.get_seeds(Player::A, 0), 0);
+        assert_eq!(after.get_seeds(Player::A, 1), 2);
+    }
+
+    #[test]
+    fn captures_pits_of_two_and_three_back_from_the_last() {
+        let after = played(
+            board(Rules::default(), [0, 0, 0, 0, 0, 2], [1, 2, 1, 1, 1, 1], Player::A),
+            5,
+        );
+        assert_eq!(after.score(Player::A), 5);
+        assert_eq!(after.pits[1], [0, 0, 1, 1, 1, 1]);
+        assert_eq!(after.next_player(), Player::B);
+        // the run stops at a pit of any other size
+        let after = played(
+            board(Rules::default(), [0, 0, 0, 0, 0, 2], [3, 1, 1, 1, 1, 1], Player::A),
+            5,
+        );
+        assert_eq!(after.score(Player::A), 2);
+        assert_eq!(after.pits[1], [4, 0, 1, 1, 1, 1]);
+    }
+
+    /// `A` taking every seed of `B` with pit 5, or playing pit 0 instead
+    fn grand_slam(rule: GrandSlam) -> OwareBoard<6> {
+        board(Rules::Oware(rule), [1, 0, 0, 0, 0, 2], [1, 2, 0, 0, 0, 0], Player::A)
+    }
+
+    #[test]
+    fn grand_slam_captures_nothing() {
+        let after = played(grand_slam(GrandSlam::NoCapture), 5);
+        assert_eq!(after.score(Player::A), 0);
+        assert_eq!(after.pits[1], [2, 3, 0, 0, 0, 0]);
+        assert!(after.outcome().is_none());
+    }
+
+    #[test]
+    fn grand_slam_forbidden() {
+        let board = grand_slam(GrandSlam::Forbidden);
+        assert!(!board.is_available_move(5));
+        assert!(board.is_available_move(0));
+    }
+
+    #[test]
+    fn grand_slam_to_opponent() {
+        let after = played(grand_slam(GrandSlam::ToOpponent), 5);
+        assert_eq!(after.score(Player::B), 5);
+        assert_eq!(after.pits[1], [0; 6]);
+        assert_eq!(after.outcome(), Some(Outcome::WonBy(Player::B)));
+    }
+
+    #[test]
+    fn must_feed_an_empty_opponent() {
+        let board = board(Rules::default(), [1, 0, 0, 0, 0, 1], [0; 6], Player::A);
+        assert!(!board.is_available_move(0));
+        assert!(board.is_available_move(5));
+    }
+
+    #[test]
+    fn stuck_player_ends_the_game() {
+        // `A` can not feed `B` after this, so each side keeps its seeds
+        let after = played(board(Rules::default(), [0; 6], [0, 0, 0, 0, 0, 1], Player::B), 5);
+        assert_eq!(after.outcome(), Some(Outcome::WonBy(Player::A)));
+        assert_eq!((after.score(Player::A), after.score(Player::B)), (1, 0));
+        assert_eq!(after.pits, [[0; 6]; 2]);
+    }
+
+    #[test]
+    fn quiet_limit_ends_the_game() {
+        let mut board = OwareBoard::<6>::new(4);
+        board.quiet_plies = QUIET_LIMIT - 1;
+        let after = played(board, 0);
+        assert_eq!(after.outcome(), Some(Outcome::Draw));
+        assert_eq!((after.score(Player::A), after.score(Player::B)), (24, 24));
+    }
+
+    #[test]
+    fn kalah_last_seed_in_store_moves_again() {
+        let board = OwareBoard::<6>::with_rules(4, Rules::Kalah);
+        assert_eq!(board.path(2), vec![3, 4, 5, 12]);
+        let after = played(board, 2);
+        assert_eq!(after.score(Player::A), 1);
+        assert_eq!(after.next_player(), Player::A);
+    }
+
+    #[test]
+    fn kalah_captures_opposite_pit() {
+        let board = board(Rules::Kalah, [1, 0, 0, 0, 0, 2], [1, 0, 0, 0, 3, 0], Player::A);
+        let after = played(board, 0);
+        assert_eq!(after.score(Player::A), 4);
+        assert_eq!(after.pits, [[0, 0, 0, 0, 0, 2], [1, 0, 0, 0, 0, 0]]);
+        assert_eq!(after.next_player(), Player::B);
+    }
+}
@@ -0,0 +1,167 @@
+//! Opening books: weighted moves for positions early in the game
+//!
+//! A book is plain text, with the board it is for in `[Tag value]` lines like game
+//! records, followed by a line per position:
+//!
+//! ```text
+//! [Pits 6]
+//! [Seeds 4]
+//! [Rules Abapa]
+//!
+//! 4 4 4 4 4 4 / 4 4 4 4 4 4 / 0 0 A: A3 12, A5 4, A6 1
+//! 4 4 0 5 5 5 / 5 4 4 4 4 4 / 0 0 B: B1 6, B5 2
+//! ```
+//!
+//! A position is `A`'s pits, `B`'s pits, both stores and the side to move, and is followed
+//! by moves written as in records, each with how often it should be picked relative to the
+//! others.
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use bevy::log::{info, warn};
+use board_game::board::{Board, Player};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use super::record::{tag, BoardTags};
+use super::{OwareBoard, Rules, Setup, Turn};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Book {
+    pub pits: usize,
+    pub seeds: u8,
+    pub rules: Rules,
+    /// Moves and their weights, by `Book::key` of the position they are played from
+    positions: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+impl Book {
+    /// Where `Ai::BookRollout` and `Ai::BookMcts` bots look for their book,
+    /// relative to the working directory
+    pub const FILE: &str = "oware-book.txt";
+
+    pub fn new(pits: usize, seeds: u8, rules: Rules) -> Self {
+        Self {
+            pits,
+            seeds,
+            rules,
+            positions: BTreeMap::new(),
+        }
+    }
+    /// The book in `Book::FILE`, read on first use
+    pub fn shared() -> Option<&'static Self> {
+        static SHARED: OnceLock<Option<Book>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| {
+                let book = std::fs::read_to_string(Self::FILE)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| text.parse::<Self>());
+                match book {
+                    Ok(book) => {
+                        info!("Loaded {} book positions", book.positions.len());
+                        Some(book)
+                    }
+                    Err(e) => {
+                        warn!("No opening book in {}: {e}", Self::FILE);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    fn key<const P: usize>(board: &OwareBoard<P>) -> String {
+        Setup::new(board).to_string()
+    }
+    /// The book is for games on this kind of board
+    fn covers<const P: usize>(&self, board: &OwareBoard<P>) -> bool {
+        P == self.pits && board.init_seeds() == self.seeds && board.rules() == self.rules
+    }
+    /// Add `weight` to the move `mv` from `board`
+    pub fn add<const P: usize>(&mut self, board: &OwareBoard<P>, mv: usize, weight: u32) {
+        if !self.covers(board) || weight == 0 {
+            return;
+        }
+        let moves = self.positions.entry(Self::key(board)).or_default();
+        match moves.iter_mut().find(|(m, _)| *m == mv) {
+            Some((_, w)) => *w += weight,
+            None => moves.push((mv, weight)),
+        }
+    }
+    /// Moves of the book for `board` and their weights
+    pub fn moves<const P: usize>(&self, board: &OwareBoard<P>) -> Option<&[(usize, u32)]> {
+        self.covers(board)
+            .then(|| self.positions.get(&Self::key(board)))
+            .flatten()
+            .map(Vec::as_slice)
+    }
+    /// A book move for `board`, picked at random in proportion to the weights
+    pub fn pick<const P: usize>(&self, board: &OwareBoard<P>, rng: &mut impl Rng) -> Option<usize> {
+        let moves: Vec<_> = self
+            .moves(board)?
+            .iter()
+            .filter(|&&(mv, _)| board.is_available_move(mv))
+            .collect();
+        let weights = WeightedIndex::new(moves.iter().map(|(_, w)| w)).ok()?;
+        Some(moves[weights.sample(rng)].0)
+    }
+}
+
+impl Display for Book {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[Pits {}]", self.pits)?;
+        writeln!(f, "[Seeds {}]", self.seeds)?;
+        writeln!(f, "[Rules {}]\n", self.rules)?;
+        for (key, moves) in &self.positions {
+            let player = if key.ends_with('A') { Player::A } else { Player::B };
+            let moves: Vec<_> = moves
+                .iter()
+                .map(|&(mv, weight)| format!("{} {weight}", Turn(player, mv)))
+                .collect();
+            writeln!(f, "{key}: {}", moves.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Book {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut board = BoardTags::default();
+        let mut positions = BTreeMap::new();
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(tag) = tag(line) {
+                let (key, value) = tag?;
+                board.read(key, value)?;
+                continue;
+            }
+            let (key, moves) = line
+                .split_once(':')
+                .ok_or_else(|| format!("book line {line:?} has no moves"))?;
+            let moves = moves
+                .split(',')
+                .map(|m| {
+                    let (turn, weight) = m.trim().split_once(' ').unwrap_or((m.trim(), ""));
+                    let Turn(_, mv) = turn.parse()?;
+                    let weight = weight
+                        .parse()
+                        .map_err(|_| format!("invalid weight {weight:?} in {line:?}"))?;
+                    Ok((mv, weight))
+                })
+                .collect::<Result<_, String>>()?;
+            positions.insert(key.trim().to_string(), moves);
+        }
+        board.check()?;
+        Ok(Self {
+            positions,
+            ..Self::new(board.pits, board.seeds, board.rules)
+        })
+    }
+}
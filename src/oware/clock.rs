@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use board_game::board::{Board, Outcome, Player};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{History, Oware, OwarePlugin};
+use crate::menu::OwareCfg;
+use crate::GameState;
+
+/// How much thinking time each player gets, in seconds
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum TimeControl {
+    #[default]
+    Untimed,
+    /// Base time, and an increment added after each move
+    Fischer(u64, u64),
+    /// Fresh time for every move, unused time is lost
+    PerMove(u64),
+}
+impl TimeControl {
+    /// Time controls offered in the menu
+    pub const ALL: [Self; 6] = [
+        Self::Untimed,
+        Self::Fischer(180, 2),
+        Self::Fischer(300, 3),
+        Self::Fischer(600, 0),
+        Self::PerMove(10),
+        Self::PerMove(30),
+    ];
+    pub fn name(&self) -> String {
+        match self {
+            Self::Untimed => "Untimed".to_string(),
+            Self::Fischer(base, 0) => format!("{} min", base / 60),
+            Self::Fischer(base, inc) => format!("{} min + {inc} s", base / 60),
+            Self::PerMove(secs) => format!("{secs} s per move"),
+        }
+    }
+    fn start(&self) -> Option<Duration> {
+        match self {
+            Self::Untimed => None,
+            Self::Fischer(secs, _) | Self::PerMove(secs) => Some(Duration::from_secs(*secs)),
+        }
+    }
+}
+
+/// `untimed`, `fischer:BASE:INC` or `permove:SECS`
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Untimed => write!(f, "untimed"),
+            Self::Fischer(base, inc) => write!(f, "fischer:{base}:{inc}"),
+            Self::PerMove(secs) => write!(f, "permove:{secs}"),
+        }
+    }
+}
+impl FromStr for TimeControl {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let secs = |n: &str| n.parse().map_err(|_| format!("invalid seconds {n:?} in {s:?}"));
+        match s.split(':').collect::<Vec<_>>()[..] {
+            ["untimed"] => Ok(Self::Untimed),
+            ["fischer", base, inc] => Ok(Self::Fischer(secs(base)?, secs(inc)?)),
+            ["permove", n] => Ok(Self::PerMove(secs(n)?)),
+            _ => Err(format!("unknown time control {s:?}")),
+        }
+    }
+}
+
+/// Time left on each player's clock
+#[derive(Resource)]
+pub struct Clocks {
+    /// For `Player::A` and `Player::B`, `None` when untimed
+    pub left: [Option<Duration>; 2],
+    control: TimeControl,
+    /// Moves the clocks have seen, and the time left as each position was reached,
+    /// which undo and redo go back to
+    moves: Vec<usize>,
+    saved: Vec<[Option<Duration>; 2]>,
+    /// Position the clocks are running for, as an index into `saved`
+    at: usize,
+}
+impl Default for Clocks {
+    fn default() -> Self {
+        Self::new(TimeControl::default())
+    }
+}
+impl Clocks {
+    pub fn new(control: TimeControl) -> Self {
+        let left = [control.start(); 2];
+        Self {
+            left,
+            control,
+            moves: vec![],
+            saved: vec![left],
+            at: 0,
+        }
+    }
+    /// Credit the player who just finished a move
+    fn moved(&mut self, player: Player) {
+        let left = &mut self.left[player.index() as usize];
+        match self.control {
+            TimeControl::Untimed => (),
+            TimeControl::Fischer(_, inc) => *left = left.map(|l| l + Duration::from_secs(inc)),
+            TimeControl::PerMove(_) => *left = self.control.start(),
+        }
+    }
+    /// Catch up with the game after `moves`, the last of them played by `player`
+    fn follow(&mut self, moves: &[usize], player: Option<Player>) {
+        if moves == &self.moves[..self.at] {
+            return;
+        }
+        match moves.split_last() {
+            // a move from the position on the clocks, even the one undone, runs on the
+            // time left now
+            Some((&mv, before)) if before == &self.moves[..self.at] => {
+                self.moves.truncate(self.at);
+                self.saved.truncate(self.at + 1);
+                if let Some(player) = player {
+                    self.moved(player);
+                }
+                self.moves.push(mv);
+                self.saved.push(self.left);
+            }
+            // a game loaded without its clocks starts from the times left now
+            _ => {
+                self.moves = moves.to_vec();
+                self.saved = vec![self.left; moves.len() + 1];
+            }
+        }
+        self.at = moves.len();
+    }
+    /// Undo and redo find the clocks as they were after `plies` moves
+    pub fn step(&mut self, plies: usize) {
+        if let Some(&left) = self.saved.get(plies) {
+            self.left = left;
+            self.at = plies;
+        }
+    }
+}
+
+/// Clock shown beside a store, for the player index it holds
+#[derive(Component)]
+pub struct ClockText(pub usize);
+
+impl<const P: usize> OwarePlugin<P> {
+    /// Run the clock of the player to move, who loses when it runs out
+    pub(super) fn tick_clocks(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut cfg: ResMut<OwareCfg>,
+        board: Res<Oware<P>>,
+        history: Res<History<P>>,
+        mut clocks: ResMut<Clocks>,
+    ) {
+        let mover = history.turns().last().map(|(player, _)| player);
+        clocks.follow(history.moves(), mover);
+        let player = board.next_player();
+        let Some(left) = clocks.left[player.index() as usize].as_mut() else {
+            return;
+        };
+        *left = left.saturating_sub(time.delta());
+        if left.is_zero() {
+            cfg.outcome = Some(Outcome::WonBy(player.other()));
+            cfg.on_time = true;
+            commands.insert_resource(NextState(Some(GameState::Menu)));
+        }
+    }
+    pub(super) fn show_clocks(clocks: Res<Clocks>, mut texts: Query<(&mut Text, &ClockText)>) {
+        texts.for_each_mut(|(mut text, clock)| {
+            let Some(left) = clocks.left[clock.0] else {
+                return;
+            };
+            let secs = left.as_secs_f32().ceil() as u64;
+            let value = format!("{}:{:02}", secs / 60, secs % 60);
+            let section = &mut text.sections[0];
+            if section.value != value {
+                section.value = value;
+                section.style.color = if secs <= 10 { Color::RED } else { Color::BLACK };
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn think(clocks: &mut Clocks, player: Player, secs: u64) {
+        let left = clocks.left[player.index() as usize].as_mut().unwrap();
+        *left -= Duration::from_secs(secs);
+    }
+    fn secs(clocks: &Clocks) -> [u64; 2] {
+        clocks.left.map(|l| l.unwrap().as_secs())
+    }
+
+    #[test]
+    fn replaying_an_undone_move_keeps_the_time_spent() {
+        let mut clocks = Clocks::new(TimeControl::Fischer(60, 2));
+        think(&mut clocks, Player::A, 5);
+        clocks.follow(&[0], Some(Player::A));
+        think(&mut clocks, Player::B, 10);
+        clocks.follow(&[0, 7], Some(Player::B));
+        assert_eq!(secs(&clocks), [57, 52]);
+        // undo, think again, and play the same move
+        clocks.step(1);
+        clocks.follow(&[0], Some(Player::A));
+        assert_eq!(secs(&clocks), [57, 60]);
+        think(&mut clocks, Player::B, 20);
+        clocks.follow(&[0, 7], Some(Player::B));
+        assert_eq!(secs(&clocks), [57, 42]);
+        // the times saved for the move are those of the replay
+        clocks.step(1);
+        clocks.step(2);
+        assert_eq!(secs(&clocks), [57, 42]);
+    }
+}
@@ -1,38 +1,60 @@
-use std::ops::Deref;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy::{prelude::*, utils::Instant};
+use board_game::{ai::simple::RandomBot, ai::Bot, board::Player};
+#[cfg(not(target_arch = "wasm32"))]
+use futures_lite::future;
+use rand::Rng;
+use std::fmt::{self, Display};
+use std::str::FromStr;
 
-use bevy::prelude::*;
-use board_game::{
-    ai::mcts::MCTSBot,
-    ai::simple::{RandomBot, RolloutBot},
-    ai::Bot,
-    board::Player,
-    games::oware::OwareBoard,
-};
-
-use super::Ai;
+use super::search::{MctsBot, RolloutBot};
+use super::{minmax::MinMaxBot, Ai, Book, OwareBoard, Tablebase};
 
 #[derive(Component)]
 pub struct Moved;
 
+/// Bot move search running in the background, attached to the bot's store
+#[derive(Component)]
+pub struct Thinking(pub Search);
+
+/// Move search on the async compute pool, which runs on the main thread of the web
+/// build, where the move is searched right away instead
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Search(Task<Option<usize>>);
+#[cfg(target_arch = "wasm32")]
+pub struct Search(Option<Option<usize>>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Search {
+    pub fn start<const P: usize>(actor: Actor, board: OwareBoard<P>) -> Self {
+        Self(AsyncComputeTaskPool::get().spawn(async move { actor.get_mv(&board) }))
+    }
+    /// The move found, once the search is over
+    pub fn poll(&mut self) -> Option<Option<usize>> {
+        future::block_on(future::poll_once(&mut self.0))
+    }
+}
+#[cfg(target_arch = "wasm32")]
+impl Search {
+    pub fn start<const P: usize>(actor: Actor, board: OwareBoard<P>) -> Self {
+        Self(Some(actor.get_mv(&board)))
+    }
+    /// The move found, once the search is over
+    pub fn poll(&mut self) -> Option<Option<usize>> {
+        self.0.take()
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct MoveBall(pub Bowl, pub usize);
 
 // #[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 #[derive(Component, Debug, Clone, Deref, DerefMut, PartialEq, Eq)]
 pub struct Bowl(pub usize);
-impl Bowl {
-    pub fn next(&self, skip: &Self, wrap: usize) -> Self {
-        let next_move = Self((self.deref() + 1) % wrap);
-        if next_move.eq(skip) {
-            next_move.next(skip, wrap)
-        } else {
-            next_move
-        }
-    }
-}
 
 // #[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Actor {
     Bot(Ai),
     Human,
@@ -42,21 +64,73 @@ impl Actor {
     pub fn is_human(&self) -> bool {
         !matches!(self, Self::Bot(_))
     }
+    pub fn name(&self) -> String {
+        match self {
+            Self::Human => "Human".to_string(),
+            Self::Bot(ai) => ai.name(),
+        }
+    }
     pub fn get_mv<const P: usize>(&self, board: &OwareBoard<P>) -> Option<usize> {
         match self {
             Self::Human => None,
+            Self::Bot(Ai::Level(level)) => {
+                let blunder = rand::thread_rng().gen_bool(level.blunder());
+                Self::Bot(if blunder { Ai::Random } else { level.ai() }).get_mv(board)
+            }
             Self::Bot(ai) => {
-                let rng = rand::thread_rng();
+                let mut rng = rand::thread_rng();
+                let deadline = Instant::now() + ai.budget();
+                let book = ai.uses_book().then(Book::shared).flatten();
+                if let Some(mv) = book.and_then(|book| book.pick(board, &mut rng)) {
+                    return Some(mv);
+                }
                 Some(match ai {
                     Ai::Random => RandomBot::new(rng).select_move(board),
-                    Ai::Rollout(r) => RolloutBot::new(*r, rng).select_move(board),
-                    Ai::Mcts(i, ew) => MCTSBot::new(*i as u64, *ew as f32, rng).select_move(board),
-                    _ => unimplemented!("Ai moves"),
+                    Ai::Rollout(r) | Ai::BookRollout(r) | Ai::CustomRollout(r) => {
+                        RolloutBot::new(*r, rng)
+                            .with_deadline(deadline)
+                            .select_move(board)
+                    }
+                    Ai::Mcts(i, ew) | Ai::BookMcts(i, ew) | Ai::CustomMcts(i, ew) => {
+                        MctsBot::new(*i as u64, *ew as f32, rng)
+                            .with_deadline(deadline)
+                            .select_move(board)
+                    }
+                    Ai::MinMax(d) => MinMaxBot::new(*d as u32)
+                        .with_deadline(deadline)
+                        .select_move(board),
+                    Ai::Endgame(d) => Tablebase::shared()
+                        .and_then(|table| table.best_move(board))
+                        .unwrap_or_else(|| {
+                            MinMaxBot::new(*d as u32)
+                                .with_deadline(deadline)
+                                .select_move(board)
+                        }),
+                    Ai::Level(_) => unreachable!("levels pick a bot above"),
                 })
             }
         }
     }
 }
 
+/// `human` or a bot spec
+impl Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Human => write!(f, "human"),
+            Self::Bot(ai) => write!(f, "{ai}"),
+        }
+    }
+}
+impl FromStr for Actor {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            bot => bot.parse().map(Self::Bot),
+        }
+    }
+}
+
 #[derive(Component, Deref, Debug)]
 pub struct PC(pub Player);
@@ -0,0 +1,63 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use super::Ai;
+
+/// Named bot strengths offered to players, weakest first
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Difficulty {
+    Beginner,
+    Novice,
+    Intermediate,
+    Strong,
+    Expert,
+    Master,
+}
+impl Difficulty {
+    pub const ALL: [Self; 6] = [
+        Self::Beginner,
+        Self::Novice,
+        Self::Intermediate,
+        Self::Strong,
+        Self::Expert,
+        Self::Master,
+    ];
+    /// The bot playing the level's moves, when it does not blunder
+    ///
+    /// None of them need the opening book or the tablebase, which may be missing.
+    pub fn ai(&self) -> Ai {
+        match self {
+            Self::Beginner => Ai::Rollout(9),
+            Self::Novice => Ai::Rollout(81),
+            Self::Intermediate => Ai::Mcts(243, 1),
+            Self::Strong => Ai::Mcts(729, 2),
+            Self::Expert => Ai::MinMax(6),
+            Self::Master => Ai::MinMax(10),
+        }
+    }
+    /// Chance of a random move instead of the bot's
+    pub fn blunder(&self) -> f64 {
+        match self {
+            Self::Beginner => 0.35,
+            Self::Novice => 0.2,
+            Self::Intermediate => 0.08,
+            Self::Strong => 0.03,
+            Self::Expert | Self::Master => 0.,
+        }
+    }
+}
+/// Level names as used in bot specs, in lower case
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{self:?}").to_lowercase())
+    }
+}
+impl FromStr for Difficulty {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.to_string() == s)
+            .ok_or_else(|| format!("unknown difficulty {s:?}"))
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use board_game::board::{Board, Player};
+
+use super::{
+    Bowl, Clocks, History, Layout, Oware, OwareBoard, OwarePlugin, Position, RebuildBoard, PC,
+};
+use crate::loading::{sprite, BoardAssets};
+use crate::menu::{Actions, OwareCfg};
+use crate::GameState;
+
+/// Side to move and what keeps the position from being played, under the board
+#[derive(Component)]
+pub struct EditorStatus;
+
+/// Why the edited position can not be played: the seeds must add up to those of a
+/// normal game, nobody may have won already, and the side to move needs a move
+pub fn check<const P: usize>(board: &OwareBoard<P>, init_seeds: u8) -> Result<(), String> {
+    let total: usize = (0..2 * P + 2).map(|b| board.seeds_at(b) as usize).sum();
+    let expected = 2 * P * init_seeds as usize;
+    if total != expected {
+        return Err(format!("{total} of {expected} seeds placed"));
+    }
+    if let Some(p) = Player::BOTH
+        .into_iter()
+        .find(|&p| 2 * board.score(p) as usize > total)
+    {
+        return Err(format!("{p:?} already has a majority"));
+    }
+    if !(0..P).any(|mv| board.is_available_move(mv)) {
+        return Err(format!("{:?} has no move", board.next_player()));
+    }
+    Ok(())
+}
+
+impl<const P: usize> OwarePlugin<P> {
+    /// Start editing from the usual starting position
+    pub(super) fn start_editor(
+        cfg: Res<OwareCfg>,
+        mut board: ResMut<Oware<P>>,
+        mut history: ResMut<History<P>>,
+        mut clocks: ResMut<Clocks>,
+    ) {
+        board.0 = OwareBoard::with_rules(cfg.init_seeds, cfg.rules);
+        *history = History::new(board.0.clone());
+        *clocks = Clocks::default();
+    }
+    /// A click on a bowl adds a seed, a right click takes one
+    pub(super) fn edit(
+        mut commands: Commands,
+        assets: Res<BoardAssets>,
+        layout: Res<Layout>,
+        mouse: Res<Input<MouseButton>>,
+        mut board: ResMut<Oware<P>>,
+        mut bowls: Query<(&Bowl, &mut Interaction, &Transform), With<PC>>,
+        seeds: Query<(Entity, &Bowl), Without<PC>>,
+    ) {
+        let take = mouse.just_released(MouseButton::Right);
+        bowls.for_each_mut(|(bowl, mut interaction, tr)| {
+            let add = *interaction == Interaction::Clicked;
+            if !add && !(take && *interaction == Interaction::Hovered) {
+                return;
+            }
+            *interaction = Interaction::Hovered;
+            let n = board.seeds_at(**bowl);
+            if add && n < u8::MAX {
+                board.set_seeds(**bowl, n + 1);
+                let seed = sprite(&assets.meatball, layout.size / 4., *tr);
+                commands.spawn(seed).insert(bowl.clone());
+            } else if !add && n > 0 {
+                board.set_seeds(**bowl, n - 1);
+                if let Some((e, _)) = seeds.iter().find(|(_, b)| *b == bowl) {
+                    commands.entity(e).despawn();
+                }
+            }
+        });
+    }
+    /// Actions of the editor menu, with Tab to switch sides and Enter to play
+    pub(super) fn editor_actions(
+        mut commands: Commands,
+        mut events: EventReader<Actions>,
+        kbd: Res<Input<KeyCode>>,
+        mut cfg: ResMut<OwareCfg>,
+        mut game: Position<P>,
+        mut rebuild: EventWriter<RebuildBoard>,
+    ) {
+        let Position {
+            board,
+            history,
+            clocks,
+        } = &mut game;
+        let keys = [
+            (KeyCode::Tab, Actions::SwitchSides),
+            (KeyCode::Return, Actions::PlayFromHere),
+        ];
+        let actions: Vec<_> = events
+            .iter()
+            .copied()
+            .chain(
+                keys.into_iter()
+                    .filter(|(k, _)| kbd.just_pressed(*k))
+                    .map(|(_, action)| action),
+            )
+            .collect();
+        for action in actions {
+            match action {
+                Actions::SwitchSides => {
+                    let player = board.next_player().other();
+                    board.set_next_player(player);
+                }
+                Actions::ClearBoard => {
+                    (0..2 * P + 2).for_each(|b| board.set_seeds(b, 0));
+                    rebuild.send(RebuildBoard);
+                }
+                Actions::PlayFromHere => match check(&board.0, cfg.init_seeds) {
+                    Ok(()) => {
+                        cfg.clear_outcome();
+                        **history = History::new(board.0.clone());
+                        **clocks = Clocks::new(cfg.time_control);
+                        commands.insert_resource(NextState(Some(GameState::Game)));
+                    }
+                    Err(e) => warn!("Can not play this position: {e}"),
+                },
+                _ => (),
+            }
+        }
+    }
+    pub(super) fn show_editor_status(
+        mut commands: Commands,
+        assets: Res<BoardAssets>,
+        cfg: Res<OwareCfg>,
+        layout: Res<Layout>,
+        board: Res<Oware<P>>,
+        mut status: Query<(&mut Text, &mut Transform), With<EditorStatus>>,
+    ) {
+        let value = match check(&board.0, cfg.init_seeds) {
+            Ok(()) => format!("{:?} to move, ready to play", board.next_player()),
+            Err(e) => format!("{:?} to move, {e}", board.next_player()),
+        };
+        let at = layout.status().extend(1.);
+        let Ok((mut text, mut tr)) = status.get_single_mut() else {
+            let text = assets.text(
+                value,
+                layout.size / 3.,
+                Color::BLACK,
+                Transform::from_translation(at),
+            );
+            commands.spawn((text, EditorStatus));
+            return;
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+        if tr.translation != at {
+            tr.translation = at;
+            text.sections[0].style.font_size = layout.size / 3.;
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use board_game::board::Board;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use super::{Bowl, History, Layout, Moved, Oware, OwarePlugin, PC};
+use crate::loading::{sprite, BoardAssets};
+use crate::menu::OwareCfg;
+use crate::tweens::*;
+use crate::GameState;
+
+const LEGAL: Color = Color::WHITE;
+const HOVERED: Color = Color::rgb(1., 0.85, 0.55);
+const ILLEGAL: Color = Color::rgb(0.55, 0.55, 0.55);
+const OUTLINE: Color = Color::rgba(1., 0.6, 0.1, 0.8);
+const CAPTURED: Color = Color::rgb(0.9, 0.1, 0.1);
+
+/// `TweenCompleted::user_data` of indicators that are despawned when their tween ends
+pub const FADED: u64 = 4;
+
+/// Outline behind the pit the last move was played from
+#[derive(Component)]
+pub struct LastMove;
+
+/// Flash a pit whose seeds are being captured
+pub fn flash(commands: &mut Commands, assets: &BoardAssets, layout: &Layout, at: Vec2) {
+    let mut overlay = sprite(&assets.bowl, layout.size, Transform::from_translation(at.extend(2.)));
+    overlay.sprite.color = CAPTURED.with_a(0.);
+    let tween = BeTween::with_lerp(Duration::from_millis(480), |s: &mut Sprite, _, r| {
+        s.color.set_a(0.8 * (2. * PI * r).sin().abs());
+    })
+    .with_completed_event(FADED);
+    commands.spawn((overlay, Animator::new(tween)));
+}
+
+/// "+N" rising and fading over a store that gained seeds
+pub fn float_score(
+    commands: &mut Commands,
+    assets: &BoardAssets,
+    layout: &Layout,
+    at: Vec2,
+    seeds: usize,
+) {
+    let rise = layout.size;
+    let text = assets.text(
+        format!("+{seeds}"),
+        layout.size / 2.,
+        CAPTURED,
+        Transform::from_translation(at.extend(3.)),
+    );
+    let float = BeTween::with_lerp(
+        Duration::from_millis(1200),
+        move |tr: &mut Transform, start, r| tr.translation.y = start.translation.y + rise * r,
+    )
+    .with_completed_event(FADED);
+    let fade = BeTween::with_lerp(Duration::from_millis(1200), |t: &mut Text, _, r| {
+        t.sections[0].style.color.set_a(1. - r);
+    });
+    commands.spawn((text, Animator::new(float), Animator::new(fade)));
+}
+
+impl<const P: usize> OwarePlugin<P> {
+    /// While a human is to move, dim the pits that can't be played and tint the hovered one
+    pub(super) fn highlight_bowls(
+        board: Res<Oware<P>>,
+        cfg: Res<OwareCfg>,
+        state: Res<State<GameState>>,
+        moving: Query<(), With<Moved>>,
+        mut bowls: Query<(&Bowl, &Interaction, &mut Sprite), With<PC>>,
+    ) {
+        let player = board.next_player();
+        let human_turn = state.0 == GameState::Game
+            && cfg.is_human(player)
+            && !board.is_done()
+            && moving.is_empty();
+        bowls.for_each_mut(|(bowl, interaction, mut sprite)| {
+            let legal =
+                **bowl / P == player.index() as usize && board.is_available_move(**bowl % P);
+            let color = match (**bowl < 2 * P && human_turn, legal, interaction) {
+                (false, ..) => LEGAL,
+                (true, false, _) => ILLEGAL,
+                (true, true, Interaction::Hovered) => HOVERED,
+                (true, true, _) => LEGAL,
+            };
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        });
+    }
+    /// Outline the pit of the last move whenever the history moves or the board is laid out
+    pub(super) fn outline_last_move(
+        mut commands: Commands,
+        assets: Res<BoardAssets>,
+        layout: Res<Layout>,
+        history: Res<History<P>>,
+        outlines: Query<Entity, With<LastMove>>,
+    ) {
+        if !history.is_changed() && !layout.is_changed() {
+            return;
+        }
+        outlines.for_each(|e| commands.entity(e).despawn());
+        if let Some((player, mv)) = history.turns().last() {
+            let at = layout.pit(player.index() as usize, mv, P).extend(0.5);
+            let transform = Transform::from_translation(at);
+            let mut outline = sprite(&assets.bowl, layout.size * 1.3, transform);
+            outline.sprite.color = OUTLINE;
+            commands.spawn((outline, LastMove));
+        }
+    }
+    pub(super) fn despawn_faded(
+        mut commands: Commands,
+        mut completed: EventReader<TweenCompleted>,
+    ) {
+        for e in completed.iter().filter(|e| e.user_data == FADED) {
+            commands.entity(e.entity).despawn_recursive();
+        }
+    }
+}
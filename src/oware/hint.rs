@@ -0,0 +1,123 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use board_game::board::Board;
+use std::time::Duration;
+
+use super::{Actor, Bowl, Layout, Oware, OwareBoard, OwarePlugin, Search, PC};
+use crate::loading::BoardAssets;
+use crate::menu::{Actions, OwareCfg};
+use crate::tweens::*;
+
+/// Hint search running in the background, attached to the store of the player asking
+#[derive(Component)]
+pub struct Hinting<const P: usize> {
+    pub search: Search,
+    /// Position searched, the hint is dropped if the board differs once it is found
+    pub board: OwareBoard<P>,
+}
+
+/// The suggested bowl, pulsing until a move is played, and the capture label above it
+#[derive(Component)]
+pub struct Hint;
+
+/// Hints being searched for or shown, there is at most one at a time
+#[derive(SystemParam)]
+pub(super) struct Hints<'w, 's, const P: usize> {
+    pending: Query<'w, 's, (), With<Hinting<P>>>,
+    shown: Query<'w, 's, (), With<Hint>>,
+}
+impl<const P: usize> Hints<'_, '_, P> {
+    fn exist(&self) -> bool {
+        !self.pending.is_empty() || !self.shown.is_empty()
+    }
+}
+
+impl<const P: usize> OwarePlugin<P> {
+    /// Start a hint search on `Actions::Hint` or H, only for a human to move
+    pub(super) fn request_hint(
+        mut commands: Commands,
+        mut events: EventReader<Actions>,
+        kbd: Res<Input<KeyCode>>,
+        cfg: Res<OwareCfg>,
+        board: Res<Oware<P>>,
+        stores: Query<(Entity, &Bowl), With<PC>>,
+        hints: Hints<P>,
+    ) {
+        let asked = events.iter().any(|e| *e == Actions::Hint);
+        if !(asked || kbd.just_pressed(KeyCode::H))
+            || !cfg.is_human(board.next_player())
+            || hints.exist()
+        {
+            return;
+        }
+        let store = Bowl(2 * P + board.next_player().index() as usize);
+        let Some((entity, _)) = stores.iter().find(|(_, b)| **b == store) else {
+            return;
+        };
+        let ai = Actor::Bot(cfg.hint);
+        commands.entity(entity).insert(Hinting {
+            search: Search::start(ai, board.0.clone()),
+            board: board.0.clone(),
+        });
+    }
+    /// Pulse the suggested bowl once the search is done, labelled with the seeds it captures
+    pub(super) fn show_hint(
+        mut commands: Commands,
+        assets: Res<BoardAssets>,
+        layout: Res<Layout>,
+        board: Res<Oware<P>>,
+        mut hinting: Query<(Entity, &PC, &mut Hinting<P>)>,
+        bowls: Query<(Entity, &Bowl), With<PC>>,
+    ) {
+        let Ok((entity, player, mut hinting)) = hinting.get_single_mut() else {
+            return;
+        };
+        let Some(mv) = hinting.search.poll() else {
+            return;
+        };
+        commands.entity(entity).remove::<Hinting<P>>();
+        // the position moved on while searching, maybe to the same player after an extra turn
+        let Some(mv) = mv.filter(|_| board.0 == hinting.board) else {
+            return;
+        };
+        let mut after = board.0.clone();
+        after.play(mv);
+        let captures = after.score(**player).saturating_sub(board.score(**player));
+        let bowl = Bowl(mv + P * player.index() as usize);
+        let Some((entity, _)) = bowls.iter().find(|(_, b)| **b == bowl) else {
+            return;
+        };
+        let pulse = BeTween::with_lerp(Duration::from_millis(800), |tr: &mut Transform, start, r| {
+            tr.scale = start.scale * (1. + 0.2 * (r * std::f32::consts::PI).sin());
+        })
+        .with_repeat_count(RepeatCount::Infinite);
+        commands
+            .entity(entity)
+            .insert((Hint, Animator::new(pulse)))
+            .with_children(|p| {
+                p.spawn(assets.text(
+                    format!("+{captures}"),
+                    layout.size / 2.,
+                    Color::ORANGE_RED,
+                    Transform::from_xyz(0., 0., 2.),
+                ))
+                .insert(Hint);
+            });
+    }
+    /// Drop the hint as soon as a move starts or the board is rebuilt
+    pub(super) fn clear_hint(
+        mut commands: Commands,
+        mut hinted: Query<(Entity, &mut Transform, Option<&Bowl>), With<Hint>>,
+    ) {
+        hinted.for_each_mut(|(entity, mut tr, bowl)| {
+            if bowl.is_some() {
+                tr.scale = Vec3::ONE;
+                commands
+                    .entity(entity)
+                    .remove::<(Hint, Animator<Transform>)>();
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+        });
+    }
+}
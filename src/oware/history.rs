@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use board_game::board::{Board, Player};
+
+use super::OwareBoard;
+
+/// Every position of the current game and the moves played between them
+///
+/// Positions after the cursor are kept for redo until a different move is played.
+#[derive(Resource, Debug, Clone)]
+pub struct History<const P: usize> {
+    boards: Vec<OwareBoard<P>>,
+    moves: Vec<usize>,
+    cursor: usize,
+}
+impl<const P: usize> Default for History<P> {
+    fn default() -> Self {
+        Self::new(OwareBoard::default())
+    }
+}
+impl<const P: usize> History<P> {
+    pub fn new(board: OwareBoard<P>) -> Self {
+        Self {
+            boards: vec![board],
+            moves: vec![],
+            cursor: 0,
+        }
+    }
+    pub fn initial(&self) -> &OwareBoard<P> {
+        &self.boards[0]
+    }
+    pub fn current(&self) -> &OwareBoard<P> {
+        &self.boards[self.cursor]
+    }
+    /// Positions from the start of the game up to the current one
+    pub fn positions(&self) -> &[OwareBoard<P>] {
+        &self.boards[..=self.cursor]
+    }
+    /// Moves leading to the current position
+    pub fn moves(&self) -> &[usize] {
+        &self.moves[..self.cursor]
+    }
+    /// Moves leading to the current position, with the player who made them
+    pub fn turns(&self) -> impl Iterator<Item = (Player, usize)> + '_ {
+        self.boards
+            .iter()
+            .zip(self.moves())
+            .map(|(board, &mv)| (board.next_player(), mv))
+    }
+    /// The move played from the current position on the redo line
+    pub fn next_move(&self) -> Option<usize> {
+        self.moves.get(self.cursor).copied()
+    }
+    /// Record `mv` played from the current position, resulting in `board`
+    pub fn push(&mut self, mv: usize, board: OwareBoard<P>) {
+        if self.moves.get(self.cursor) != Some(&mv) {
+            self.moves.truncate(self.cursor);
+            self.boards.truncate(self.cursor + 1);
+            self.moves.push(mv);
+            self.boards.push(board);
+        }
+        self.cursor += 1;
+    }
+    pub fn undo(&mut self) -> Option<&OwareBoard<P>> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        Some(self.current())
+    }
+    pub fn redo(&mut self) -> Option<&OwareBoard<P>> {
+        if self.cursor == self.moves.len() {
+            return None;
+        }
+        self.cursor += 1;
+        Some(self.current())
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+
+use super::{Moved, OwarePlugin, RebuildBoard};
+
+/// Bowl size and positions, fitted to the window the board was last built for
+#[derive(Resource)]
+pub struct Layout {
+    /// Width of a pit, stores are twice as large
+    pub size: f32,
+    /// Center of the board, off the window's center when a side panel takes room
+    origin: Vec2,
+    /// The window was resized since the board was built
+    stale: bool,
+}
+impl Default for Layout {
+    fn default() -> Self {
+        Self::fit(600., 800., 6, 0.)
+    }
+}
+impl Layout {
+    /// Fit a row of `pits` with a margin across, and both stores with the menu along the height,
+    /// leaving `panel` pixels free on the right
+    pub fn fit(width: f32, height: f32, pits: usize, panel: f32) -> Self {
+        Self {
+            size: ((width - panel) / (pits + 2) as f32).min(height / 16.),
+            origin: Vec2::new(-panel / 2., 0.),
+            stale: false,
+        }
+    }
+    /// Distance of `Player::B`'s row from the middle, `Player::A`'s mirrors it
+    fn row(&self) -> f32 {
+        self.size * 0.64
+    }
+    pub fn store(&self, player: usize) -> Vec2 {
+        self.origin + Vec2::new(0., self.dir(player) * self.row() * 4.)
+    }
+    pub fn pit(&self, player: usize, mv: usize, pits: usize) -> Vec2 {
+        let x = pits as f32 / 2. - mv as f32 - 0.5;
+        self.origin + Vec2::new(self.dir(player) * x * self.size, self.dir(player) * self.row())
+    }
+    /// Below `Player::A`'s store, for the position editor's status
+    pub fn status(&self) -> Vec2 {
+        self.origin + Vec2::new(0., -self.row() * 8.)
+    }
+    /// Offset of a bowl's seed count from its center, away from the middle of the board
+    pub fn label(&self, player: usize) -> f32 {
+        self.dir(player) * self.row()
+    }
+    fn dir(&self, player: usize) -> f32 {
+        if player == 0 {
+            -1.
+        } else {
+            1.
+        }
+    }
+}
+
+impl<const P: usize> OwarePlugin<P> {
+    /// Rebuild the board for the new window size once no seeds are in flight
+    pub(super) fn relayout(
+        mut resized: EventReader<WindowResized>,
+        mut layout: ResMut<Layout>,
+        moving: Query<(), With<Moved>>,
+        mut rebuild: EventWriter<RebuildBoard>,
+    ) {
+        if resized.iter().last().is_some() {
+            layout.stale = true;
+        }
+        if layout.stale && moving.is_empty() {
+            layout.stale = false;
+            rebuild.send(RebuildBoard);
+        }
+    }
+}
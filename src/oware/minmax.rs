@@ -0,0 +1,131 @@
+use bevy::utils::Instant;
+use board_game::{
+    ai::Bot,
+    board::{Board, Outcome, Player},
+};
+
+use super::OwareBoard;
+
+const WIN: i32 = 1 << 16;
+const MATERIAL: i32 = 8;
+const MOBILITY: i32 = 1;
+
+/// Deterministic negamax bot with alpha-beta pruning, searching `depth` plies
+///
+/// The search deepens iteratively, so when a deadline is set the move from the
+/// deepest completed iteration is played.
+#[derive(Debug, Clone, Copy)]
+pub struct MinMaxBot {
+    depth: u32,
+    deadline: Option<Instant>,
+}
+
+impl MinMaxBot {
+    pub fn new(depth: u32) -> Self {
+        Self {
+            depth: depth.max(1),
+            deadline: None,
+        }
+    }
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+    /// Material and mobility balance from the point of view of the player to move
+    pub fn eval<const P: usize>(board: &OwareBoard<P>) -> i32 {
+        let me = board.next_player();
+        let pits = |p: Player| (0..P).filter(|&mv| board.get_seeds(p, mv) > 0).count() as i32;
+        let material = board.score(me) as i32 - board.score(me.other()) as i32;
+        MATERIAL * material + MOBILITY * (pits(me) - pits(me.other()))
+    }
+    fn moves<const P: usize>(board: &OwareBoard<P>) -> impl Iterator<Item = usize> + '_ {
+        (0..P).filter(|&mv| board.is_available_move(mv))
+    }
+    fn timed_out(&self) -> bool {
+        self.deadline.map_or(false, |d| Instant::now() > d)
+    }
+    /// `None` when the deadline passed before the subtree was searched
+    fn negamax<const P: usize>(
+        &self,
+        board: &OwareBoard<P>,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        if let Some(outcome) = board.outcome() {
+            // prefer quick wins and slow losses
+            return Some(match outcome {
+                Outcome::Draw => 0,
+                Outcome::WonBy(p) if p == board.next_player() => WIN - ply,
+                Outcome::WonBy(_) => ply - WIN,
+            });
+        }
+        if depth == 0 {
+            return Some(Self::eval(board));
+        }
+        if self.timed_out() {
+            return None;
+        }
+        let mut best = -WIN;
+        for mv in Self::moves(board) {
+            let mut child = board.clone();
+            child.play(mv);
+            let value = self.child_value(board, &child, depth - 1, ply + 1, alpha, beta)?;
+            best = best.max(value);
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+        Some(best)
+    }
+    /// Value of `child` for the player to move on `board`, who may move again (Kalah)
+    fn child_value<const P: usize>(
+        &self,
+        board: &OwareBoard<P>,
+        child: &OwareBoard<P>,
+        depth: u32,
+        ply: i32,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        if child.next_player() == board.next_player() {
+            self.negamax(child, depth, ply, alpha, beta)
+        } else {
+            Some(-self.negamax(child, depth, ply, -beta, -alpha)?)
+        }
+    }
+    fn search<const P: usize>(&self, board: &OwareBoard<P>, depth: u32) -> Option<usize> {
+        let (mut alpha, beta) = (-WIN - 1, WIN + 1);
+        let mut best = None;
+        for mv in Self::moves(board) {
+            let mut child = board.clone();
+            child.play(mv);
+            let value = self.child_value(board, &child, depth - 1, 1, alpha, beta)?;
+            // strict comparison keeps the lowest pit on ties, so play is reproducible
+            if best.is_none() || value > alpha {
+                alpha = value;
+                best = Some(mv);
+            }
+        }
+        best
+    }
+}
+
+impl<const P: usize> Bot<OwareBoard<P>> for MinMaxBot {
+    fn select_move(&mut self, board: &OwareBoard<P>) -> usize {
+        let mut best = Self::moves(board)
+            .next()
+            .expect("select_move called on a finished game");
+        for depth in 1..=self.depth {
+            match self.search(board, depth) {
+                Some(mv) => best = mv,
+                None => break,
+            }
+        }
+        best
+    }
+}
@@ -1,19 +1,51 @@
 use crate::loading::{sprite, BoardAssets};
-use crate::menu::OwareCfg;
+use crate::menu::{Actions, OwareCfg};
 use crate::tweens::*;
-use crate::GameState;
+#[cfg(target_arch = "wasm32")]
+use crate::launch::share_query;
+use crate::{audio::Sound, despawn_with, launch::Launch, on_board, GameState};
+use bevy::ecs::system::SystemParam;
 use bevy::input::touch::TouchPhase;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
-use board_game::{
-    board::{Board, Player},
-    games::oware::OwareBoard,
-};
+use bevy::window::PrimaryWindow;
+use board_game::board::{Board, Player};
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 use std::time::Duration;
 
+mod board;
+mod book;
+mod clock;
 mod components;
+mod difficulty;
+mod editor;
+mod highlight;
+mod hint;
+mod history;
+mod layout;
+mod minmax;
+mod move_list;
+mod record;
+mod replay;
+mod search;
+mod tablebase;
+pub use board::{GrandSlam, OwareBoard, Rules, QUIET_LIMIT};
+pub use book::Book;
+pub use clock::{Clocks, TimeControl};
 pub use components::*;
-const SIZE: f32 = 50.;
+pub use difficulty::Difficulty;
+pub use history::History;
+pub use layout::Layout;
+pub use record::{GameRecord, Setup, Turn};
+pub use replay::Replay;
+pub use tablebase::Tablebase;
+
+use clock::ClockText;
+use editor::EditorStatus;
+use highlight::{flash, float_score};
+use move_list::MOVE_LIST_WIDTH;
 
 // #[cfg_attr(feature = "dev", derive(bevy_inspector_egui::quick::ResourceInspectable))]
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
@@ -22,84 +54,290 @@ pub enum Ai {
     Random,
     Rollout(u32),
     Mcts(u32, u8),
-    _MinMax,
+    MinMax(u8),
+    /// Plays from the endgame tablebase once the position is in it, minmax before that
+    Endgame(u8),
+    /// `Rollout` and `Mcts` playing from the opening book while the position is in it
+    BookRollout(u32),
+    BookMcts(u32, u8),
+    /// A named difficulty, its bot sometimes blundering on purpose
+    Level(Difficulty),
+    /// `Rollout` and `Mcts` with the parameters of the "Custom Bot" screen, which follow
+    /// the screen as it is tuned
+    CustomRollout(u32),
+    CustomMcts(u32, u8),
+}
+/// Parses bot specs like `random`, `rollout:729`, `mcts:27:1`, `minmax:6`, `endgame:8`,
+/// `book-rollout:729`, `book-mcts:729:2`, `custom-rollout:729`, `custom-mcts:729:2` or a
+/// difficulty like `novice`, where missing numbers take the values of the default menu bots
+impl FromStr for Ai {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn arg<T: FromStr>(part: Option<&str>, default: T, spec: &str) -> Result<T, String> {
+            part.map_or(Ok(default), |a| {
+                a.parse().map_err(|_| format!("invalid number {a:?} in bot {spec:?}"))
+            })
+        }
+        let mut parts = s.split(':');
+        let ai = match parts.next().unwrap_or_default() {
+            "random" => Self::Random,
+            "rollout" => Self::Rollout(arg(parts.next(), 27, s)?),
+            "mcts" => Self::Mcts(arg(parts.next(), 27, s)?, arg(parts.next(), 1, s)?),
+            "minmax" => Self::MinMax(arg(parts.next(), 4, s)?),
+            "endgame" => Self::Endgame(arg(parts.next(), 8, s)?),
+            "book-rollout" => Self::BookRollout(arg(parts.next(), 729, s)?),
+            "book-mcts" => Self::BookMcts(arg(parts.next(), 729, s)?, arg(parts.next(), 2, s)?),
+            "custom-rollout" => Self::CustomRollout(arg(parts.next(), 729, s)?),
+            "custom-mcts" => {
+                Self::CustomMcts(arg(parts.next(), 729, s)?, arg(parts.next(), 2, s)?)
+            }
+            name => match name.parse() {
+                Ok(level) => Self::Level(level),
+                Err(_) => return Err(format!("unknown bot {name:?}")),
+            },
+        };
+        match parts.next() {
+            Some(_) => Err(format!("too many numbers in bot {s:?}")),
+            None => Ok(ai),
+        }
+    }
+}
+/// Writes the spec `FromStr` reads back
+impl Display for Ai {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::Rollout(r) => write!(f, "rollout:{r}"),
+            Self::Mcts(i, ew) => write!(f, "mcts:{i}:{ew}"),
+            Self::MinMax(d) => write!(f, "minmax:{d}"),
+            Self::Endgame(d) => write!(f, "endgame:{d}"),
+            Self::BookRollout(r) => write!(f, "book-rollout:{r}"),
+            Self::BookMcts(i, ew) => write!(f, "book-mcts:{i}:{ew}"),
+            Self::CustomRollout(r) => write!(f, "custom-rollout:{r}"),
+            Self::CustomMcts(i, ew) => write!(f, "custom-mcts:{i}:{ew}"),
+            Self::Level(level) => write!(f, "{level}"),
+        }
+    }
+}
+impl Ai {
+    /// Wall-clock time the bot may think, after which it plays the best move found so far
+    pub fn budget(&self) -> Duration {
+        if let Self::Level(level) = self {
+            return level.ai().budget();
+        }
+        Duration::from_millis(match self {
+            Self::Random => 500,
+            Self::Rollout(_) | Self::BookRollout(_) | Self::MinMax(_) | Self::Endgame(_) => 3000,
+            Self::Mcts(..) | Self::BookMcts(..) => 5000,
+            // tuned counts above the default 729 get time in proportion
+            Self::CustomRollout(r) => 3000 * (*r as u64 / 729).max(1),
+            Self::CustomMcts(i, _) => 5000 * (*i as u64 / 729).max(1),
+            Self::Level(_) => unreachable!("levels take their bot's budget"),
+        })
+    }
+    /// How menus show the bot: the level name, or the spec of a raw bot
+    pub fn name(&self) -> String {
+        match self {
+            Self::Level(level) => format!("{level:?}"),
+            ai => ai.to_string(),
+        }
+    }
+    pub fn uses_book(&self) -> bool {
+        matches!(self, Self::BookRollout(_) | Self::BookMcts(..))
+    }
+}
+
+/// Pace of the sowing and capture animations
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum Animation {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+    /// Seeds land in their bowl without any tween
+    Instant,
+}
+impl Animation {
+    pub const ALL: [Self; 4] = [Self::Slow, Self::Normal, Self::Fast, Self::Instant];
+    /// Multiplier on tween durations, `None` skips the tweens
+    pub fn scale(&self) -> Option<f32> {
+        match self {
+            Self::Slow => Some(2.),
+            Self::Normal => Some(1.),
+            Self::Fast => Some(0.35),
+            Self::Instant => None,
+        }
+    }
 }
 
 pub fn entities_exist_with<T: Component>(query: Query<(), With<T>>) -> bool {
     !query.is_empty()
 }
 
+/// `TweenCompleted::user_data` of a seed landing in its bowl
+const SOWN: u64 = 3;
+
+impl Display for Animation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{self:?}").to_lowercase())
+    }
+}
+impl FromStr for Animation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|a| a.to_string() == s)
+            .ok_or_else(|| format!("unknown animation {s:?}"))
+    }
+}
+
+/// Board sizes, in pits per side, that can be played
+pub const PITS: RangeInclusive<usize> = 3..=10;
+
+/// Initial seeds per pit a board with `pits` per side can start with, so that a store
+/// can hold all the seeds
+pub fn seeds_range(pits: usize) -> RangeInclusive<u8> {
+    1..=(u8::MAX as usize / (2 * pits)) as u8
+}
+
 pub struct OwarePlugin<const P: usize>;
 
+/// Rebuild bowls and seeds from the current position, without animation
+pub struct RebuildBoard;
+
+/// Game read from the save file, played out by the plugin for its board size
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource)]
+pub struct LoadGame(pub GameRecord);
+
 #[derive(Resource, Default, Deref, DerefMut)]
 pub struct Oware<const P: usize>(OwareBoard<P>);
 
+/// The position on the board, with the game that led to it and the clocks
+#[derive(SystemParam)]
+pub struct Position<'w, const P: usize> {
+    board: ResMut<'w, Oware<P>>,
+    history: ResMut<'w, History<P>>,
+    clocks: ResMut<'w, Clocks>,
+}
+
+/// Cursor, touches, clicks and number keys picking a bowl
+#[derive(SystemParam)]
+pub struct Pointer<'w, 's> {
+    cursor: EventReader<'w, 's, CursorMoved>,
+    touch: EventReader<'w, 's, TouchInput>,
+    mouse_button_inputs: Res<'w, Input<MouseButton>>,
+    kbd: Res<'w, Input<KeyCode>>,
+}
+
+/// What the board is drawn with, and where
+#[derive(SystemParam)]
+pub struct BoardView<'w, 's> {
+    assets: Res<'w, BoardAssets>,
+    layout: ResMut<'w, Layout>,
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+}
+
 impl<const P: usize> Oware<P> {
     fn is_done(board: Res<Self>) -> bool {
         board.is_done()
     }
     fn _is_bot_turn(&self, cfg: Res<OwareCfg>) -> bool {
-        self.next_player().index() == cfg.human_is_first as u8
+        !cfg.is_human(self.next_player())
     }
     pub fn seeds_in(&self, mv: &Bowl) -> u8 {
-        if **mv < 2 * P {
-            self.get_seeds(Player::BOTH[**mv / P], **mv % P)
-        } else {
-            self.score(Player::BOTH[**mv - 2 * P])
-        }
-    }
-    pub fn is_player_bowl(&self, mv: &Bowl) -> bool {
-        (if self.next_player().index() == 0 {
-            0..P
-        } else {
-            P..2 * P
-        })
-        .contains(mv)
+        self.seeds_at(**mv)
     }
 }
 impl<const P: usize> OwarePlugin<P> {
+    /// Every board size has its own plugin, only the one picked in `OwareCfg` runs
+    fn active(cfg: Option<Res<OwareCfg>>) -> bool {
+        cfg.map_or(false, |cfg| cfg.pits == P)
+    }
     fn spawn_board(
         mut commands: Commands,
-        assets: Res<BoardAssets>,
         mut cfg: ResMut<OwareCfg>,
-        mut board: ResMut<Oware<P>>,
-        balls: Query<Entity, (With<Bowl>, Without<PC>)>,
+        mut game: Position<P>,
+        mut view: BoardView,
+        entities: Query<Entity, With<Bowl>>,
     ) {
+        let Position {
+            board,
+            history,
+            clocks,
+        } = &mut game;
+        let BoardView {
+            assets,
+            layout,
+            windows,
+        } = &mut view;
         if cfg.new_game {
-            *board = Oware(OwareBoard::<P>::new(cfg.init_seeds));
+            board.0 = OwareBoard::<P>::with_rules(cfg.init_seeds, cfg.rules);
+            **history = History::new(board.0.clone());
+            **clocks = Clocks::new(cfg.time_control);
             cfg.new_game = false;
-            cfg.outcome = None;
-            balls.for_each(|e| commands.entity(e).despawn_recursive())
+            cfg.clear_outcome();
         }
-        let transform = |x, y| Transform::from_xyz(x, y, 1.);
+        if let Ok(window) = windows.get_single() {
+            let panel = if cfg.show_moves { MOVE_LIST_WIDTH } else { 0. };
+            **layout = Layout::fit(window.width(), window.height(), P, panel);
+        }
+        let size = layout.size;
+        // the board is always rebuilt from the current position
+        entities.for_each(|e| commands.entity(e).despawn_recursive());
+        let transform = |at: Vec2| Transform::from_translation(at.extend(1.));
+        let image = |bowl: &Bowl| {
+            if board.seeds_in(bowl) > 1 {
+                &assets.meatball_bowl
+            } else {
+                &assets.bowl
+            }
+        };
         Player::BOTH.iter().enumerate().for_each(|(i, &player)| {
-            let dir = if i == 0 { -1. } else { 1. };
-            let v_off = (SIZE - 18.) * dir;
+            let label = layout.label(i);
+            let store = Bowl(2 * P + i);
+            let at = layout.store(i);
+            (0..board.seeds_in(&store)).for_each(|i| {
+                commands
+                    .spawn(sprite(&assets.meatball, size / 4., transform(at)))
+                    .insert(store.clone())
+                    .insert(Name::new(format!("Seed{player:?}Score{i}")));
+            });
             commands
-                .spawn(sprite(&assets.bowl, 100., transform(0., v_off * 4.)))
+                .spawn(sprite(image(&store), 2. * size, transform(at)))
                 .insert(PC(player))
-                .insert(Bowl(2 * P + i))
+                .insert(store)
                 .insert(Interaction::None)
                 .insert(Name::new(format!("Bowl{player:?}Score")))
                 .with_children(|p| {
                     p.spawn(assets.text(
                         format!("{}", board.score(player)),
-                        SIZE / 2.,
+                        size / 2.,
                         Color::BLACK,
-                        transform(0., v_off * 2.),
+                        transform(Vec2::new(0., label * 2.)),
                     ));
+                    if clocks.left[i].is_some() {
+                        p.spawn(assets.text(
+                            "",
+                            size / 2.,
+                            Color::BLACK,
+                            transform(Vec2::new(size * 1.8, 0.)),
+                        ))
+                        .insert(ClockText(i));
+                    }
                 });
             (0..P).for_each(|mv| {
-                let h = dir * (SIZE * P as f32 / 2. - mv as f32 * SIZE - SIZE / 2.);
+                let at = layout.pit(i, mv, P);
                 let bowl = Bowl(mv + P * i);
-                (0..board.init_seeds()).for_each(|i| {
+                (0..board.seeds_in(&bowl)).for_each(|i| {
                     commands
-                        .spawn(sprite(&assets.meatball, SIZE / 4., transform(h, v_off)))
+                        .spawn(sprite(&assets.meatball, size / 4., transform(at)))
                         .insert(bowl.clone())
                         .insert(Name::new(format!("Seed{}", mv * 4 + i as usize)));
                 });
                 commands
-                    .spawn(sprite(&assets.meatball_bowl, SIZE, transform(h, v_off)))
+                    .spawn(sprite(image(&bowl), size, transform(at)))
                     .insert(bowl)
                     .insert(PC(Player::BOTH[i]))
                     .insert(Interaction::None)
@@ -107,15 +345,15 @@ impl<const P: usize> OwarePlugin<P> {
                     .with_children(|parent| {
                         parent.spawn(assets.text(
                             format!("{}", board.get_seeds(Player::A, mv),),
-                            SIZE / 2.,
+                            size / 2.,
                             Color::BLACK,
-                            transform(0., v_off),
+                            transform(Vec2::new(0., label)),
                         ));
                         parent.spawn(assets.text(
                             format!("{}", mv + 1),
-                            SIZE / 3.,
+                            size / 3.,
                             Color::rgba_u8(175, 163, 163, 255),
-                            transform(0., -v_off / 2.),
+                            transform(Vec2::new(0., -label / 2.)),
                         ));
                     });
             })
@@ -123,14 +361,20 @@ impl<const P: usize> OwarePlugin<P> {
     }
     fn sow(
         mut commands: Commands,
+        cfg: Res<OwareCfg>,
         mut board: ResMut<Oware<P>>,
+        mut history: ResMut<History<P>>,
+        layout: Res<Layout>,
         bowls: Query<(&Bowl, &Transform, Option<&Moved>, Entity), With<PC>>,
-        balls: Query<(Entity, &Bowl, Option<&MoveBall>), (Without<PC>, Without<Moved>)>,
+        mut balls: Query<
+            (Entity, &Bowl, Option<&MoveBall>, &mut Transform),
+            (Without<PC>, Without<Moved>),
+        >,
     ) {
         let bowl_map: HashMap<usize, &Transform> = bowls.iter().map(|x| (**x.0, x.1)).collect();
 
         if let Some((sowed_bowl, entity)) = bowls.iter().find_map(|x| x.2.map(|_| (x.0, x.3))) {
-            let mut next_bowl = sowed_bowl.next(sowed_bowl, 2 * P);
+            let mv = sowed_bowl.wrapping_rem(P);
             balls
                 .iter()
                 .filter_map(|x| {
@@ -140,30 +384,40 @@ impl<const P: usize> OwarePlugin<P> {
                         None
                     }
                 })
+                .zip(board.path(mv))
                 .enumerate()
-                .for_each(|(i, e)| {
-                    commands.entity(e).insert(MoveBall(next_bowl.clone(), i));
-                    next_bowl = next_bowl.next(sowed_bowl, 2 * P);
+                .for_each(|(i, (e, to))| {
+                    commands.entity(e).insert(MoveBall(Bowl(to), i));
                 });
             commands.entity(entity).remove::<Moved>();
-            board.play(sowed_bowl.wrapping_rem(P));
+            board.play(mv);
+            history.push(mv, board.0.clone());
         };
 
-        let millis = |i: u64, f| Duration::from_millis(1 + i * f);
-        balls.for_each(|(entity, from, movedball)| {
+        let scale = cfg.animation.scale();
+        let secs = |s: f32| Duration::from_secs_f32(s * scale.unwrap_or_default());
+        balls.for_each_mut(|(entity, from, movedball, mut tr)| {
             if let Some(MoveBall(to_bowl, nth)) = movedball {
                 let from = bowl_map.get(from).unwrap().translation;
                 let to = bowl_map.get(to_bowl).unwrap().translation;
+                if scale.is_none() {
+                    tr.translation = to;
+                    commands
+                        .entity(entity)
+                        .insert(to_bowl.clone())
+                        .remove::<MoveBall>();
+                    return;
+                }
                 let lerp = |a, b, r| a + (b - a) * r;
                 let d = to.y - from.y;
-                let p = SIZE / 2.;
+                let p = layout.size / 2.;
                 let b = 2. * (d.max(0.) + p + (p * p + p * d.abs()).sqrt());
-                let tween = Delay::new(millis(*nth as u64, 243)).then(
-                    BeTween::with_lerp(Duration::from_secs(2), move |tr: &mut Transform, _, r| {
+                let tween = Delay::new(secs(0.001 + *nth as f32 * 0.243)).then(
+                    BeTween::with_lerp(secs(2.), move |tr: &mut Transform, _, r| {
                         tr.translation.x = lerp(from.x, to.x, r);
                         tr.translation.y = from.y - (b - d) * r.powi(2) + b * r;
                     })
-                    .with_completed_event(3),
+                    .with_completed_event(SOWN),
                 );
                 commands
                     .entity(entity)
@@ -175,8 +429,10 @@ impl<const P: usize> OwarePlugin<P> {
         mut commands: Commands,
         moved: Query<&MoveBall>,
         mut completed: EventReader<TweenCompleted>,
+        mut sounds: EventWriter<Sound>,
     ) {
-        for e in completed.iter() {
+        for e in completed.iter().filter(|e| e.user_data == SOWN) {
+            sounds.send(Sound::Seed);
             let bowl = moved.get(e.entity).unwrap().0.clone();
             commands
                 .entity(e.entity)
@@ -184,33 +440,66 @@ impl<const P: usize> OwarePlugin<P> {
                 .remove::<(Moved, MoveBall)>();
         }
     }
+    /// Send seeds that left a pit on the board, but not yet on screen, to the store that gained them
     fn update_scores(
         mut commands: Commands,
+        mut sounds: EventWriter<Sound>,
+        cfg: Res<OwareCfg>,
+        assets: Res<BoardAssets>,
+        layout: Res<Layout>,
         board: Res<Oware<P>>,
-        bowls: Query<&Bowl, With<PC>>,
         balls: Query<(Entity, &Bowl, Option<&MoveBall>), Without<PC>>,
     ) {
-        if balls.iter().all(|x| x.2.is_none()) {
-            bowls
-                .iter()
-                .filter(|x| board.seeds_in(x) == 0 && board.is_player_bowl(x))
-                .for_each(|mv| {
-                    balls
-                        .iter()
-                        .filter(|x| x.1.eq(mv))
-                        .enumerate()
-                        .for_each(|(i, x)| {
-                            commands.entity(x.0).insert(MoveBall(
-                                Bowl(board.next_player().other().index() as usize + 2 * P),
-                                i,
-                            ));
-                        })
-                })
+        if balls.iter().any(|x| x.2.is_some()) {
+            return;
         }
+        let mut on_screen = HashMap::<usize, Vec<Entity>>::new();
+        balls.for_each(|(e, bowl, _)| on_screen.entry(**bowl).or_default().push(e));
+        let count = |bowl: usize| on_screen.get(&bowl).map_or(0, Vec::len);
+        let gaining: Vec<_> = (2 * P..2 * P + 2)
+            .filter(|&store| board.seeds_at(store) as usize > count(store))
+            .collect();
+        let mut gained = HashMap::<usize, usize>::new();
+        let animated = cfg.animation.scale().is_some();
+        (0..2 * P).for_each(|pit| {
+            let extra = count(pit).saturating_sub(board.seeds_at(pit) as usize);
+            if extra == 0 {
+                return;
+            }
+            // when both stores gain, as at the end of a game, seeds go to the pit's owner
+            let store = match gaining[..] {
+                [store] => store,
+                _ => 2 * P + pit / P,
+            };
+            *gained.entry(store).or_default() += extra;
+            // the first two delay slots leave time for the pit to flash
+            let slots = if animated {
+                flash(&mut commands, &assets, &layout, layout.pit(pit / P, pit % P, P));
+                2
+            } else {
+                0
+            };
+            on_screen
+                .get(&pit)
+                .into_iter()
+                .flatten()
+                .take(extra)
+                .enumerate()
+                .for_each(|(i, &e)| {
+                    commands.entity(e).insert(MoveBall(Bowl(store), i + slots));
+                });
+        });
+        if !gained.is_empty() {
+            sounds.send(Sound::Capture);
+        }
+        gained.into_iter().for_each(|(store, seeds)| {
+            let at = layout.store(store - 2 * P);
+            float_score(&mut commands, &assets, &layout, at, seeds);
+        });
     }
     fn update_bowls(
         assets: Res<BoardAssets>,
-        mut bowls: Query<(&Children, &PC, &Bowl, &mut Handle<Image>)>,
+        mut bowls: Query<(&Children, &PC, &Bowl, &mut Handle<Image>, Option<&Thinking>)>,
         cfg: Res<OwareCfg>,
         mut text: Query<&mut Text>,
         mut map: Local<HashMap<usize, bool>>,
@@ -223,13 +512,17 @@ impl<const P: usize> OwarePlugin<P> {
                 *count += 1;
                 m
             });
-        bowls.for_each_mut(|(ch, player, mv, mut img, ..)| {
+        bowls.for_each_mut(|(ch, player, mv, mut img, thinking)| {
             let mut text = text.get_mut(ch[0]).unwrap();
             let &seeds = ball_count.get(mv).unwrap_or(&0);
             text.sections[0].value = format!(
                 "{}{seeds}",
                 if (2 * P).le(mv) {
-                    format!("{:?}\n", cfg.get_actor(player.0))
+                    format!(
+                        "{}{}\n",
+                        cfg.get_actor(player.0).name(),
+                        if thinking.is_some() { " thinking..." } else { "" }
+                    )
                 } else {
                     "".to_string()
                 }
@@ -250,26 +543,49 @@ impl<const P: usize> OwarePlugin<P> {
         board: Res<Oware<P>>,
         cfg: Res<OwareCfg>,
         bowls: Query<(Entity, &Interaction, &Bowl, &PC)>,
+        mut thinking: Query<(Entity, &mut Thinking)>,
         time: Res<Time>,
         mut timer: Local<Timer>,
     ) {
-        if board.next_player().index() == cfg.human_is_first as u8 {
-            if timer.duration() == Duration::ZERO {
-                *timer = Timer::new(Duration::from_millis(1729), TimerMode::Repeating);
-            }
-            if !timer.tick(time.delta()).just_finished() {
+        let actor = cfg.get_actor(board.next_player());
+        let mv = if let Actor::Bot(_) = actor {
+            if let Ok((entity, mut thinking)) = thinking.get_single_mut() {
+                // searches stop at the bot's budget with the best move found by then
+                let Some(mv) = thinking.0.poll() else {
+                    return;
+                };
+                commands.entity(entity).remove::<Thinking>();
+                mv
+            } else {
+                let delay = Duration::from_millis(cfg.bot_delay as u64);
+                if timer.duration() != delay {
+                    *timer = Timer::new(delay, TimerMode::Repeating);
+                }
+                if delay.is_zero() || timer.tick(time.delta()).just_finished() {
+                    let store = bowls
+                        .iter()
+                        .find(|e| (2 * P + board.next_player().index() as usize).eq(e.2))
+                        .unwrap()
+                        .0;
+                    let search = Search::start(actor, board.0.clone());
+                    commands.entity(store).insert(Thinking(search));
+                }
                 return;
             }
-        }
-        let actor = cfg.get_actor(board.next_player());
-        if let Some(mv) = if actor.is_human() {
-            bowls
-                .iter()
-                .find(|e| e.1 == &Interaction::Clicked && cfg.is_human(e.3 .0))
-                .map(|(_, _, Bowl(v), ..)| *v % P)
         } else {
-            actor.get_mv(&board)
-        } {
+            let clicked = bowls.iter().find(|e| {
+                e.1 == &Interaction::Clicked && e.3 .0 == board.next_player() && **e.2 < 2 * P
+            });
+            if let Some((bowl, ..)) = clicked.filter(|e| !board.is_available_move(**e.2 % P)) {
+                // the click is consumed, so the bowl shakes once
+                let shake = shake_seq(Duration::from_millis(600));
+                commands
+                    .entity(bowl)
+                    .insert((Animator::new(shake), Interaction::None));
+            }
+            clicked.map(|(_, _, Bowl(v), ..)| *v % P)
+        };
+        if let Some(mv) = mv {
             if board.is_available_move(mv) {
                 let bowl = bowls
                     .iter()
@@ -280,18 +596,155 @@ impl<const P: usize> OwarePlugin<P> {
             }
         }
     }
-    fn conclude_game(mut commands: Commands, mut cfg: ResMut<OwareCfg>, board: Res<Oware<P>>) {
+    /// Undo or redo moves, stopping at the next position with a human to move
+    ///
+    /// The keys only work while playing or paused, not once the game is over, and leave
+    /// the pause menu like its Undo and Redo do.
+    fn step_history(
+        mut commands: Commands,
+        mut events: EventReader<Actions>,
+        kbd: Res<Input<KeyCode>>,
+        state: Res<State<GameState>>,
+        mut cfg: ResMut<OwareCfg>,
+        mut game: Position<P>,
+        mut rebuild: EventWriter<RebuildBoard>,
+    ) {
+        let ctrl = kbd.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+        let key = if cfg.outcome.is_some() {
+            None
+        } else if ctrl && kbd.just_pressed(KeyCode::Z) {
+            Some(true)
+        } else if ctrl && kbd.just_pressed(KeyCode::Y) {
+            Some(false)
+        } else {
+            None
+        };
+        let Some(undo) = events.iter().fold(key, |step, e| match e {
+            Actions::Undo => Some(true),
+            Actions::Redo => Some(false),
+            _ => step,
+        }) else {
+            return;
+        };
+        let Position {
+            board,
+            history,
+            clocks,
+        } = &mut game;
+        let mut changed = false;
+        while let Some(position) = if undo { history.undo() } else { history.redo() } {
+            changed = true;
+            // without humans at the board every move is a step
+            if cfg.is_human(position.next_player())
+                || !Player::BOTH.into_iter().any(|p| cfg.is_human(p))
+            {
+                break;
+            }
+        }
+        if changed {
+            // a game lost on time, or otherwise over, goes on
+            cfg.clear_outcome();
+            board.0 = history.current().clone();
+            clocks.step(history.moves().len());
+            rebuild.send(RebuildBoard);
+            if state.0 == GameState::Menu {
+                commands.insert_resource(NextState(Some(GameState::Game)));
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_game(mut events: EventReader<Actions>, history: Res<History<P>>) {
+        use record::SAVE_FILE;
+        if !events.iter().any(|e| *e == Actions::Save) {
+            return;
+        }
+        let record = GameRecord::new(&history);
+        match std::fs::write(SAVE_FILE, record.to_string()) {
+            Ok(()) => info!("Saved game to {SAVE_FILE}"),
+            Err(e) => error!("Could not save game to {SAVE_FILE}: {e}"),
+        }
+    }
+    /// Play out the game read by the menu's Load, before the board is drawn
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_game(
+        mut commands: Commands,
+        load: Res<LoadGame>,
+        cfg: Res<OwareCfg>,
+        mut game: Position<P>,
+    ) {
+        commands.remove_resource::<LoadGame>();
+        match load.0.replay::<P>() {
+            Ok(loaded) => {
+                *game.history = loaded;
+                game.board.0 = game.history.current().clone();
+                *game.clocks = Clocks::new(cfg.time_control);
+            }
+            Err(e) => error!("Could not load game from {}: {e}", record::SAVE_FILE),
+        }
+    }
+    /// Put a link to the current game on the clipboard
+    #[cfg(target_arch = "wasm32")]
+    fn share_link(mut events: EventReader<Actions>, cfg: Res<OwareCfg>, history: Res<History<P>>) {
+        if !events.iter().any(|e| *e == Actions::ShareLink) {
+            return;
+        }
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let location = window.location();
+        let url = format!(
+            "{}{}?{}",
+            location.origin().unwrap_or_default(),
+            location.pathname().unwrap_or_default(),
+            share_query(&GameRecord::new(&history), &cfg)
+        );
+        // the promise is dropped, copying finishes on its own
+        let _ = window.navigator().clipboard().write_text(&url);
+        info!("Share link: {url}");
+    }
+    /// Continue the game given at launch, before the board is first drawn
+    fn load_launched(
+        mut launch: ResMut<Launch>,
+        cfg: Res<OwareCfg>,
+        mut board: ResMut<Oware<P>>,
+        mut history: ResMut<History<P>>,
+        mut clocks: ResMut<Clocks>,
+    ) {
+        let Some(record) = launch.load.take() else {
+            return;
+        };
+        match record.replay::<P>() {
+            Ok(loaded) => {
+                *history = loaded;
+                board.0 = history.current().clone();
+                *clocks = Clocks::new(cfg.time_control);
+            }
+            Err(e) => error!("Could not load the game given at launch: {e}"),
+        }
+    }
+    fn conclude_game(
+        mut commands: Commands,
+        mut cfg: ResMut<OwareCfg>,
+        board: Res<Oware<P>>,
+        mut sounds: EventWriter<Sound>,
+    ) {
+        sounds.send(Sound::GameEnd);
         cfg.outcome = board.outcome();
         commands.insert_resource(NextState(Some(GameState::Menu)));
     }
     fn focus(
         mut pos: Local<Vec2>,
-        mut cursor: EventReader<CursorMoved>,
-        mut touch: EventReader<TouchInput>,
-        mouse_button_inputs: Res<Input<MouseButton>>,
-        kbd: Res<Input<KeyCode>>,
-        mut actors: Query<(Entity, &GlobalTransform, Option<&Bowl>, &mut Interaction), With<PC>>,
+        mut pointer: Pointer,
+        board: Res<Oware<P>>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+        mut actors: Query<(&GlobalTransform, &Sprite, Option<&Bowl>, &mut Interaction), With<PC>>,
     ) {
+        let Pointer {
+            cursor,
+            touch,
+            mouse_button_inputs,
+            kbd,
+        } = &mut pointer;
         if cursor.is_empty() && !kbd.is_changed() && !mouse_button_inputs.is_changed() {
             return;
         }
@@ -305,6 +758,12 @@ impl<const P: usize> OwarePlugin<P> {
             }),
             |x| x.position,
         );
+        let Ok((camera, camera_transform)) = cameras.get_single() else {
+            return;
+        };
+        let world = camera
+            .viewport_to_world(camera_transform, *pos)
+            .map(|ray| ray.origin.truncate());
 
         let k = kbd.get_just_released().find_map(|x| match x {
             KeyCode::Key1 => Some(0),
@@ -316,18 +775,18 @@ impl<const P: usize> OwarePlugin<P> {
             KeyCode::Key7 => Some(6),
             KeyCode::Key8 => Some(7),
             KeyCode::Key9 => Some(8),
+            KeyCode::Key0 => Some(9),
             _ => None,
         });
-        actors.for_each_mut(|mut e| {
-            let on_bowl = Vec2::new(
-                pos.x - e.1.translation().x - 300.,
-                pos.y - e.1.translation().y - 400.,
-            )
-            .length()
-                < SIZE / 2.;
-            *e.3 = if k.map_or(
+        // number keys pick a pit of the side to move, never a store
+        let side = board.next_player().index() as usize;
+        let keyed = |bowl: &Bowl| **bowl < 2 * P && **bowl / P == side;
+        actors.for_each_mut(|(tr, sprite, bowl, mut interaction)| {
+            let radius = sprite.custom_size.map_or(0., |s| s.x / 2.);
+            let on_bowl = world.map_or(false, |w| w.distance(tr.translation().truncate()) < radius);
+            *interaction = if k.map_or(
                 on_bowl && mouse_button_inputs.just_released(MouseButton::Left),
-                |k| e.2.map_or(false, |x| k == **x % P),
+                |k| bowl.map_or(false, |x| keyed(x) && k == **x % P),
             ) {
                 Interaction::Clicked
             } else if on_bowl {
@@ -341,22 +800,110 @@ impl<const P: usize> OwarePlugin<P> {
 
 impl<const P: usize> Plugin for OwarePlugin<P> {
     fn build(&self, app: &mut App) {
-        app.add_system(Self::spawn_board.in_schedule(OnEnter(GameState::Game)))
-            .add_systems(
-                (
-                    Self::update_bowls,
-                    Self::sow,
-                    Self::focus.run_if(not(entities_exist_with::<Moved>)),
-                    Self::update_scores.run_if(not(entities_exist_with::<Moved>)),
-                    Self::play
-                        .run_if(not(Oware::<P>::is_done))
-                        .run_if(not(entities_exist_with::<Moved>)),
-                    Self::conclude_game.run_if(Oware::<P>::is_done),
-                    Self::rm_ball,
-                )
-                    .in_set(OnUpdate(GameState::Game)),
+        app.add_systems(
+            (Self::spawn_board, Self::spawn_move_list)
+                .distributive_run_if(Self::active)
+                .in_schedule(OnEnter(GameState::Game)),
+        )
+        .add_systems(
+            (
+                Self::spawn_board.run_if(on_event::<RebuildBoard>()),
+                Self::update_bowls,
+                Self::highlight_bowls,
+                Self::outline_last_move,
+                Self::show_clocks,
+                Self::sow,
+                Self::update_scores
+                    .run_if(not(entities_exist_with::<Moved>))
+                    .run_if(not(in_state(GameState::Editor))),
+                Self::rm_ball,
+            )
+                .distributive_run_if(on_board)
+                .distributive_run_if(Self::active),
+        )
+        .add_systems(
+            (
+                Self::focus.run_if(not(entities_exist_with::<Moved>)),
+                Self::play
+                    .run_if(not(Oware::<P>::is_done))
+                    .run_if(not(entities_exist_with::<Moved>)),
+                Self::conclude_game.run_if(Oware::<P>::is_done),
+                Self::tick_clocks
+                    .run_if(not(Oware::<P>::is_done))
+                    .run_if(not(entities_exist_with::<Moved>)),
+                Self::request_hint.run_if(not(Oware::<P>::is_done)),
+                Self::show_hint,
+                Self::clear_hint.run_if(entities_exist_with::<Moved>),
             )
-            .init_resource::<Oware<P>>();
+                .distributive_run_if(Self::active)
+                .in_set(OnUpdate(GameState::Game)),
+        )
+        .add_systems(
+            (Self::start_editor, Self::spawn_board)
+                .chain()
+                .distributive_run_if(Self::active)
+                .in_schedule(OnEnter(GameState::Editor)),
+        )
+        .add_systems(
+            (Self::focus, Self::edit, Self::editor_actions, Self::show_editor_status)
+                .distributive_run_if(Self::active)
+                .in_set(OnUpdate(GameState::Editor)),
+        )
+        .add_system(
+            despawn_with::<EditorStatus>
+                .run_if(Self::active)
+                .in_schedule(OnExit(GameState::Editor)),
+        )
+        .add_system(
+            Self::load_launched
+                .run_if(resource_exists::<Launch>())
+                .run_if(Self::active)
+                .in_schedule(OnExit(GameState::Loading)),
+        )
+        .add_system(
+            Self::start_replay
+                .run_if(Self::active)
+                .in_schedule(OnEnter(GameState::Replay)),
+        )
+        .add_system(
+            Self::replay
+                .run_if(Self::active)
+                .in_set(OnUpdate(GameState::Replay)),
+        )
+        .add_system(
+            Self::exit_replay
+                .run_if(Self::active)
+                .in_schedule(OnExit(GameState::Replay)),
+        )
+        .add_system(Self::relayout.run_if(on_board).run_if(Self::active))
+        .add_system(Self::despawn_faded.run_if(Self::active))
+        .add_systems(
+            (Self::update_move_list, Self::scroll_move_list, Self::show_move_list)
+                .distributive_run_if(Self::active),
+        )
+        .add_system(
+            Self::step_history
+                .run_if(in_state(GameState::Game).or_else(in_state(GameState::Menu)))
+                .run_if(Self::active)
+                .run_if(not(entities_exist_with::<Moved>)),
+        )
+        .add_event::<RebuildBoard>()
+        .init_resource::<Oware<P>>()
+        .init_resource::<History<P>>()
+        .init_resource::<Replay>()
+        .init_resource::<Layout>()
+        .init_resource::<Clocks>();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_system(Self::save_game.run_if(Self::active)).add_system(
+            Self::load_game
+                .run_if(resource_exists::<LoadGame>())
+                .run_if(Self::active)
+                .before(Self::spawn_board)
+                .in_schedule(OnEnter(GameState::Game)),
+        );
+        #[cfg(target_arch = "wasm32")]
+        app.add_system(Self::share_link.run_if(Self::active));
 
         #[cfg(feature = "dev")]
         {
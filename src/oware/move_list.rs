@@ -0,0 +1,159 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use board_game::board::Player;
+
+use super::{History, OwarePlugin, Turn};
+use crate::loading::FontAssets;
+use crate::menu::OwareCfg;
+use crate::GameState;
+
+/// Width of the move list panel, the board is fitted to the rest of the window
+pub const MOVE_LIST_WIDTH: f32 = 150.;
+
+/// Column of moves inside the panel, scrolled by moving it up
+#[derive(Component)]
+pub struct MoveList {
+    offset: f32,
+    /// Keep the last move in view, until the list is scrolled up
+    follow: bool,
+}
+
+/// Panel holding the `MoveList`
+#[derive(Component)]
+pub struct MoveListPanel;
+
+impl<const P: usize> OwarePlugin<P> {
+    pub(super) fn spawn_move_list(mut commands: Commands, panels: Query<(), With<MoveListPanel>>) {
+        if !panels.is_empty() {
+            return;
+        }
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(0.),
+                        top: Val::Px(0.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Px(MOVE_LIST_WIDTH), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::Hidden,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .insert((MoveListPanel, Name::new("MoveListPanel")))
+            .with_children(|p| {
+                p.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        flex_grow: 1.,
+                        max_size: Size::UNDEFINED,
+                        padding: UiRect::all(Val::Px(6.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(MoveList {
+                    offset: 0.,
+                    follow: true,
+                });
+            });
+    }
+    /// One line per move: ply, side and pit as labelled on the board, seeds gained and the score
+    pub(super) fn update_move_list(
+        mut commands: Commands,
+        fonts: Res<FontAssets>,
+        history: Res<History<P>>,
+        lists: Query<(Entity, Option<&Children>), With<MoveList>>,
+        added: Query<(), Added<MoveList>>,
+    ) {
+        if !history.is_changed() && added.is_empty() {
+            return;
+        }
+        let Ok((list, lines)) = lists.get_single() else {
+            return;
+        };
+        lines
+            .into_iter()
+            .flatten()
+            .for_each(|&e| commands.entity(e).despawn_recursive());
+        let style = TextStyle {
+            font: fonts.fira_sans.clone(),
+            font_size: 18.,
+            color: Color::WHITE,
+        };
+        let lines: Vec<_> = history
+            .positions()
+            .windows(2)
+            .zip(history.turns())
+            .enumerate()
+            .map(|(ply, (boards, (player, mv)))| {
+                let gained = boards[1].score(player).saturating_sub(boards[0].score(player));
+                let captures = if gained > 0 {
+                    format!(" +{gained}")
+                } else {
+                    String::new()
+                };
+                let (a, b) = (boards[1].score(Player::A), boards[1].score(Player::B));
+                let line = format!("{}. {}{captures}  {a}-{b}", ply + 1, Turn(player, mv));
+                TextBundle::from_section(line, style.clone())
+            })
+            .collect();
+        commands.entity(list).with_children(|p| {
+            lines.into_iter().for_each(|line| {
+                p.spawn(line);
+            });
+        });
+    }
+    /// Wheel scrolling, and following the last move while scrolled to the bottom
+    pub(super) fn scroll_move_list(
+        mut wheel: EventReader<MouseWheel>,
+        mut lists: Query<(&mut MoveList, &mut Style, &Parent, &Node)>,
+        nodes: Query<&Node>,
+    ) {
+        let dy: f32 = wheel
+            .iter()
+            .map(|e| match e.unit {
+                MouseScrollUnit::Line => e.y * 20.,
+                MouseScrollUnit::Pixel => e.y,
+            })
+            .sum();
+        for (mut list, mut style, parent, node) in &mut lists {
+            let Ok(panel) = nodes.get(parent.get()) else {
+                continue;
+            };
+            let max = (node.size().y - panel.size().y).max(0.);
+            if dy != 0. {
+                list.offset = (list.offset + dy).clamp(-max, 0.);
+                list.follow = list.offset <= -max;
+            } else if list.follow {
+                list.offset = -max;
+            }
+            if style.position.top != Val::Px(list.offset) {
+                style.position.top = Val::Px(list.offset);
+            }
+        }
+    }
+    /// The panel shows while the board is on screen, if enabled from the pause menu
+    pub(super) fn show_move_list(
+        cfg: Res<OwareCfg>,
+        state: Res<State<GameState>>,
+        mut panels: Query<&mut Visibility, With<MoveListPanel>>,
+    ) {
+        let on_board = matches!(state.0, GameState::Game | GameState::Replay);
+        let shown = if cfg.show_moves && on_board {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        panels.for_each_mut(|mut v| {
+            if *v != shown {
+                *v = shown;
+            }
+        });
+    }
+}
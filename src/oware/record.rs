@@ -0,0 +1,375 @@
+//! Plain-text game records
+//!
+//! A record is a few `[Tag value]` lines followed by the moves played:
+//!
+//! ```text
+//! [Pits 6]
+//! [Seeds 4]
+//! [Rules Abapa]
+//! [Setup 4 4 4 4 4 4 / 4 4 4 4 4 4 / 0 0 A]
+//! [Result A]
+//!
+//! 1. A3 B1
+//! 2. A6 B4
+//! ```
+//!
+//! `Rules` is one of `Abapa`, `GrandSlamForbidden`, `GrandSlamToOpponent` or `Kalah`.
+//! `Setup` is only there for games started from a position set up in the editor: `A`'s
+//! pits, `B`'s pits, both stores and the side to move.
+//! A move is the side that played it, `A` (first player) or `B`, and the pit it was
+//! played from, numbered from 1 like the labels under the bowls. `Result` is the
+//! winning side, `Draw`, or `*` for a game still in progress. Move numbers are only
+//! for the reader and are skipped when parsing, as are unknown tags.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use board_game::board::{Board, Outcome, Player};
+
+use super::{seeds_range, History, OwareBoard, Rules, PITS};
+
+/// Where the native build keeps its saved game
+#[cfg(not(target_arch = "wasm32"))]
+pub const SAVE_FILE: &str = "oware.txt";
+
+/// A move as written in records: side and 1-based pit number, e.g. `A3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn(pub Player, pub usize);
+impl Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}{}", self.0, self.1 + 1)
+    }
+}
+impl FromStr for Turn {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let player = match s.get(..1) {
+            Some("A" | "a") => Player::A,
+            Some("B" | "b") => Player::B,
+            _ => return Err(format!("move {s:?} does not start with a side")),
+        };
+        match s[1..].parse::<usize>() {
+            Ok(pit) if pit > 0 => Ok(Self(player, pit - 1)),
+            _ => Err(format!("move {s:?} has no pit number")),
+        }
+    }
+}
+
+/// Starting position of a game that does not start with every pit full
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    /// `A`'s pits, then `B`'s
+    pub pits: Vec<u8>,
+    pub scores: [u8; 2],
+    pub next_player: Player,
+}
+impl Setup {
+    pub fn new<const P: usize>(board: &OwareBoard<P>) -> Self {
+        Self {
+            pits: (0..2 * P).map(|b| board.seeds_at(b)).collect(),
+            scores: Player::BOTH.map(|p| board.score(p)),
+            next_player: board.next_player(),
+        }
+    }
+    /// The position set up, if it could be played: the checks of the editor apply
+    pub fn board<const P: usize>(&self, seeds: u8, rules: Rules) -> Result<OwareBoard<P>, String> {
+        if self.pits.len() != 2 * P {
+            return Err(format!("setup has {} pits, not {}", self.pits.len(), 2 * P));
+        }
+        if !seeds_range(P).contains(&seeds) {
+            return Err(format!("{seeds} seeds per pit do not fit {P} pits"));
+        }
+        let mut board = OwareBoard::with_rules(seeds, rules);
+        let bowls = self.pits.iter().chain(&self.scores);
+        bowls.enumerate().for_each(|(b, &s)| board.set_seeds(b, s));
+        board.set_next_player(self.next_player);
+        super::editor::check(&board, seeds)?;
+        Ok(board)
+    }
+    /// `board` for a game of `pits` pits, to check setups before the game is played
+    pub fn check(&self, pits: usize, seeds: u8, rules: Rules) -> Result<(), String> {
+        match pits {
+            3 => self.board::<3>(seeds, rules).map(drop),
+            4 => self.board::<4>(seeds, rules).map(drop),
+            5 => self.board::<5>(seeds, rules).map(drop),
+            6 => self.board::<6>(seeds, rules).map(drop),
+            7 => self.board::<7>(seeds, rules).map(drop),
+            8 => self.board::<8>(seeds, rules).map(drop),
+            9 => self.board::<9>(seeds, rules).map(drop),
+            10 => self.board::<10>(seeds, rules).map(drop),
+            _ => Err(format!("invalid pits {pits}")),
+        }
+    }
+}
+impl Display for Setup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = self.pits.split_at(self.pits.len() / 2);
+        let side = |s: &[u8]| s.iter().map(u8::to_string).collect::<Vec<_>>().join(" ");
+        let [score_a, score_b] = self.scores;
+        write!(
+            f,
+            "{} / {} / {score_a} {score_b} {:?}",
+            side(a),
+            side(b),
+            self.next_player
+        )
+    }
+}
+impl FromStr for Setup {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid setup {s:?}");
+        let parts: Vec<Vec<&str>> = s.split('/').map(|p| p.split_whitespace().collect()).collect();
+        let [a, b, rest] = &parts[..] else {
+            return Err(invalid());
+        };
+        let [score_a, score_b, player] = rest[..] else {
+            return Err(invalid());
+        };
+        if a.len() != b.len() {
+            return Err(invalid());
+        }
+        let number = |n: &str| n.parse::<u8>().map_err(|_| invalid());
+        Ok(Self {
+            pits: a.iter().chain(b).map(|n| number(n)).collect::<Result<_, _>>()?,
+            scores: [number(score_a)?, number(score_b)?],
+            next_player: match player {
+                "A" => Player::A,
+                "B" => Player::B,
+                _ => return Err(invalid()),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub pits: usize,
+    pub seeds: u8,
+    pub rules: Rules,
+    /// Position the game started from, when it is not the usual one
+    pub setup: Option<Setup>,
+    pub moves: Vec<Turn>,
+    pub result: Option<Outcome>,
+}
+
+impl GameRecord {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read() -> Result<Self, String> {
+        std::fs::read_to_string(SAVE_FILE)
+            .map_err(|e| e.to_string())?
+            .parse()
+    }
+    /// Record of the game up to the current position of `history`
+    pub fn new<const P: usize>(history: &History<P>) -> Self {
+        let initial = history.initial();
+        let usual = OwareBoard::with_rules(initial.init_seeds(), initial.rules());
+        Self {
+            pits: P,
+            seeds: initial.init_seeds(),
+            rules: initial.rules(),
+            setup: (*initial != usual).then(|| Setup::new(initial)),
+            moves: history.turns().map(|(p, mv)| Turn(p, mv)).collect(),
+            result: history.current().outcome(),
+        }
+    }
+    /// Play the recorded moves from the starting position, checking each one
+    pub fn replay<const P: usize>(&self) -> Result<History<P>, String> {
+        if self.pits != P {
+            return Err(format!("record is for {} pits, not {P}", self.pits));
+        }
+        let initial = match &self.setup {
+            Some(setup) => setup.board(self.seeds, self.rules)?,
+            None => OwareBoard::with_rules(self.seeds, self.rules),
+        };
+        let mut history = History::new(initial);
+        for (ply, &turn) in self.moves.iter().enumerate() {
+            let board = history.current();
+            let Turn(player, mv) = turn;
+            if board.is_done()
+                || board.next_player() != player
+                || mv >= P
+                || !board.is_available_move(mv)
+            {
+                return Err(format!("illegal move {turn} at ply {}", ply + 1));
+            }
+            let mut board = board.clone();
+            board.play(mv);
+            history.push(mv, board);
+        }
+        Ok(history)
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[Pits {}]", self.pits)?;
+        writeln!(f, "[Seeds {}]", self.seeds)?;
+        writeln!(f, "[Rules {}]", self.rules)?;
+        if let Some(setup) = &self.setup {
+            writeln!(f, "[Setup {setup}]")?;
+        }
+        writeln!(
+            f,
+            "[Result {}]\n",
+            match self.result {
+                None => "*".to_string(),
+                Some(Outcome::Draw) => "Draw".to_string(),
+                Some(Outcome::WonBy(p)) => format!("{p:?}"),
+            }
+        )?;
+        for (i, pair) in self.moves.chunks(2).enumerate() {
+            write!(f, "{}.", i + 1)?;
+            for turn in pair {
+                write!(f, " {turn}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut board = BoardTags::default();
+        let (mut setup, mut moves, mut result) = (None, vec![], None);
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(tag) = tag(line) {
+                let (key, value) = tag?;
+                if board.read(key, value)? {
+                    continue;
+                }
+                match key {
+                    "Setup" => setup = Some(value.parse::<Setup>()?),
+                    "Result" => {
+                        result = match value {
+                            "*" => None,
+                            "Draw" => Some(Outcome::Draw),
+                            "A" => Some(Outcome::WonBy(Player::A)),
+                            "B" => Some(Outcome::WonBy(Player::B)),
+                            _ => return Err(format!("invalid {key} {value:?}")),
+                        }
+                    }
+                    _ => (),
+                }
+                continue;
+            }
+            for token in line.split_whitespace().filter(|t| !t.ends_with('.')) {
+                moves.push(token.parse()?);
+            }
+        }
+        board.check()?;
+        let BoardTags { pits, seeds, rules } = board;
+        if let Some(setup) = &setup {
+            setup.check(pits, seeds, rules)?;
+        }
+        Ok(Self {
+            pits,
+            seeds,
+            rules,
+            setup,
+            moves,
+            result,
+        })
+    }
+}
+
+/// Key and value of a `[Tag value]` line, `None` for lines that are no tag
+pub(super) fn tag(line: &str) -> Option<Result<(&str, &str), String>> {
+    let tag = line.strip_prefix('[')?.strip_suffix(']')?;
+    Some(tag.split_once(' ').ok_or_else(|| format!("malformed tag {line:?}")))
+}
+
+/// Board a record or book is for, from its `Pits`, `Seeds` and `Rules` tags
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BoardTags {
+    pub pits: usize,
+    pub seeds: u8,
+    pub rules: Rules,
+}
+impl Default for BoardTags {
+    fn default() -> Self {
+        Self {
+            pits: 6,
+            seeds: 4,
+            rules: Rules::default(),
+        }
+    }
+}
+impl BoardTags {
+    /// Take the value of a board tag, `Ok(false)` for other tags
+    pub fn read(&mut self, key: &str, value: &str) -> Result<bool, String> {
+        let invalid = || format!("invalid {key} {value:?}");
+        match key {
+            "Pits" => {
+                self.pits = value
+                    .parse()
+                    .ok()
+                    .filter(|p| PITS.contains(p))
+                    .ok_or_else(invalid)?
+            }
+            "Seeds" => self.seeds = value.parse().map_err(|_| invalid())?,
+            "Rules" => self.rules = value.parse()?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+    /// `Pits` may come after `Seeds`, so the seeds are checked once every tag is read
+    pub fn check(&self) -> Result<(), String> {
+        if !seeds_range(self.pits).contains(&self.seeds) {
+            let (seeds, pits) = (self.seeds, self.pits);
+            return Err(format!("{seeds} seeds per pit do not fit {pits} pits"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORD: &str = "[Pits 6]\n[Seeds 4]\n[Rules Abapa]\n[Result *]\n\n1. A3 B1\n2. A6\n";
+
+    #[test]
+    fn text_round_trips() {
+        let record: GameRecord = RECORD.parse().unwrap();
+        let (a, b) = (Player::A, Player::B);
+        assert_eq!(record.moves, [Turn(a, 2), Turn(b, 0), Turn(a, 5)]);
+        assert_eq!(record.to_string(), RECORD);
+        let history = record.replay::<6>().unwrap();
+        assert_eq!(GameRecord::new(&history), record);
+    }
+
+    #[test]
+    fn rejects_seeds_a_store_can_not_hold() {
+        for seeds in ["0", "22", "60"] {
+            let text = RECORD.replace("[Seeds 4]", &format!("[Seeds {seeds}]"));
+            assert!(text.parse::<GameRecord>().is_err(), "{seeds} seeds");
+        }
+        // checked against the pits of the record, whichever tag comes first
+        let text = "[Seeds 13]\n[Pits 10]\n";
+        assert!(text.parse::<GameRecord>().is_err());
+        assert!("[Seeds 21]\n[Pits 6]\n".parse::<GameRecord>().is_ok());
+    }
+
+    #[test]
+    fn rejects_setups_that_can_not_be_played() {
+        let setup = |s: &str| format!("[Pits 3]\n[Seeds 2]\n[Setup {s}]\n");
+        assert!(setup("2 2 2 / 2 2 2 / 0 0 A").parse::<GameRecord>().is_ok());
+        for (bad, why) in [
+            ("2 2 2 / 2 2 / 0 0 A", "uneven sides"),
+            ("2 2 2 / 2 2 2 / 0 0 A / 1", "extra part"),
+            ("2 2 2 / 2 2 2 / 1 0 A", "seed too many"),
+            ("2 2 2 / 2 2 1 / 0 0 A", "seed too few"),
+            ("2 2 2 / 2 2 256 / 0 0 A", "pit overflows"),
+            ("200 200 200 / 200 200 200 / 0 0 A", "total overflows"),
+            ("1 0 0 / 0 0 0 / 11 0 B", "majority"),
+            ("0 0 0 / 2 2 2 / 6 0 A", "no move"),
+            ("2 2 2 2 / 2 2 2 2 / 0 0 A", "pits of another board"),
+        ] {
+            assert!(setup(bad).parse::<GameRecord>().is_err(), "{why}");
+        }
+        let setup: Setup = "0 0 0 / 2 2 2 / 6 0 A".parse().unwrap();
+        assert!(setup.board::<3>(2, Rules::default()).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use board_game::board::Board;
+use std::time::Duration;
+
+use super::{Bowl, Clocks, History, Moved, Oware, OwarePlugin, Position, RebuildBoard, PC};
+use crate::menu::Actions;
+use crate::tweens::Animator;
+
+/// Playback state of `GameState::Replay`
+#[derive(Resource)]
+pub struct Replay {
+    pub playing: bool,
+    /// Multiplier for both the pause between moves and the sowing animation
+    pub speed: f32,
+    timer: Timer,
+}
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            speed: 1.,
+            timer: Timer::new(Duration::from_millis(1729), TimerMode::Repeating),
+        }
+    }
+}
+
+/// Replay controls, from the menu or the keys, and the time between moves
+#[derive(SystemParam)]
+pub(super) struct Playback<'w, 's> {
+    events: EventReader<'w, 's, Actions>,
+    kbd: Res<'w, Input<KeyCode>>,
+    time: Res<'w, Time>,
+    replay: ResMut<'w, Replay>,
+}
+
+impl<const P: usize> OwarePlugin<P> {
+    /// Rewind to the first position of the game
+    pub(super) fn start_replay(
+        mut board: ResMut<Oware<P>>,
+        mut history: ResMut<History<P>>,
+        mut replay: ResMut<Replay>,
+        mut rebuild: EventWriter<RebuildBoard>,
+    ) {
+        while history.undo().is_some() {}
+        board.0 = history.current().clone();
+        *replay = Replay::default();
+        rebuild.send(RebuildBoard);
+    }
+    /// Jump back to the last position of the game
+    pub(super) fn exit_replay(
+        mut board: ResMut<Oware<P>>,
+        mut history: ResMut<History<P>>,
+        mut clocks: ResMut<Clocks>,
+    ) {
+        while history.redo().is_some() {}
+        board.0 = history.current().clone();
+        clocks.step(history.moves().len());
+    }
+    /// Forward steps sow like a normal move, backward steps rebuild the board instantly
+    pub(super) fn replay(
+        mut commands: Commands,
+        mut playback: Playback,
+        mut game: Position<P>,
+        bowls: Query<(Entity, &Bowl), With<PC>>,
+        moving: Query<(), With<Moved>>,
+        mut animators: Query<&mut Animator<Transform>>,
+        mut rebuild: EventWriter<RebuildBoard>,
+    ) {
+        let Playback {
+            events,
+            kbd,
+            time,
+            replay,
+        } = &mut playback;
+        let Position { board, history, .. } = &mut game;
+        let keys = [
+            (KeyCode::P, Actions::ReplayToggle),
+            (KeyCode::Comma, Actions::StepBack),
+            (KeyCode::Period, Actions::StepForward),
+            (KeyCode::Minus, Actions::Slower),
+            (KeyCode::Equals, Actions::Faster),
+        ];
+        let controls: Vec<_> = events
+            .iter()
+            .copied()
+            .chain(
+                keys.into_iter()
+                    .filter(|(k, _)| kbd.just_pressed(*k))
+                    .map(|(_, action)| action),
+            )
+            .collect();
+        let idle = moving.is_empty();
+        let mut forward = false;
+        for action in controls {
+            match action {
+                Actions::ReplayToggle => replay.playing ^= true,
+                Actions::Faster => replay.speed = (replay.speed * 2.).min(8.),
+                Actions::Slower => replay.speed = (replay.speed / 2.).max(0.25),
+                Actions::StepForward => {
+                    replay.playing = false;
+                    forward = true;
+                }
+                Actions::StepBack if idle => {
+                    replay.playing = false;
+                    if history.undo().is_some() {
+                        board.0 = history.current().clone();
+                        rebuild.send(RebuildBoard);
+                    }
+                }
+                _ => (),
+            }
+        }
+        let speed = replay.speed;
+        animators.for_each_mut(|mut a| a.set_speed(speed));
+        if !idle {
+            return;
+        }
+        if replay.playing {
+            forward |= replay
+                .timer
+                .tick(time.delta().mul_f32(speed))
+                .just_finished();
+        }
+        match history.next_move() {
+            None => replay.playing = false,
+            Some(mv) if forward => {
+                let bowl = Bowl(mv + P * board.next_player().index() as usize);
+                if let Some((e, _)) = bowls.iter().find(|(_, b)| **b == bowl) {
+                    commands.entity(e).insert(Moved);
+                }
+            }
+            Some(_) => (),
+        }
+    }
+}
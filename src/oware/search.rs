@@ -0,0 +1,264 @@
+use bevy::utils::Instant;
+use board_game::{
+    ai::Bot,
+    board::{Board, Outcome, Player},
+};
+use rand::{seq::SliceRandom, Rng};
+
+use super::OwareBoard;
+
+fn moves<const P: usize>(board: &OwareBoard<P>) -> Vec<usize> {
+    (0..P).filter(|&mv| board.is_available_move(mv)).collect()
+}
+fn timed_out(deadline: Option<Instant>) -> bool {
+    deadline.map_or(false, |d| Instant::now() > d)
+}
+/// Plays random moves to the end, and scores the game for `player`: 1 for a win, ½ for a draw
+fn playout<const P: usize>(board: &OwareBoard<P>, player: Player, rng: &mut impl Rng) -> f32 {
+    let mut board = board.clone();
+    while !board.is_done() {
+        let mv = *moves(&board).choose(rng).expect("unfinished games have moves");
+        board.play(mv);
+    }
+    match board.outcome() {
+        Some(Outcome::WonBy(p)) if p == player => 1.,
+        Some(Outcome::Draw) => 0.5,
+        _ => 0.,
+    }
+}
+
+/// Plays `rollouts` random games, spread evenly over the moves, and picks the move
+/// that did best
+///
+/// Moves take turns, so when a deadline is set the move with the best average over
+/// the games played by then is picked.
+#[derive(Debug)]
+pub struct RolloutBot<R> {
+    rollouts: u32,
+    deadline: Option<Instant>,
+    rng: R,
+}
+
+impl<R: Rng> RolloutBot<R> {
+    pub fn new(rollouts: u32, rng: R) -> Self {
+        Self {
+            rollouts,
+            deadline: None,
+            rng,
+        }
+    }
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+}
+
+impl<const P: usize, R: Rng + std::fmt::Debug> Bot<OwareBoard<P>> for RolloutBot<R> {
+    fn select_move(&mut self, board: &OwareBoard<P>) -> usize {
+        let moves = moves(board);
+        assert!(!moves.is_empty(), "select_move called on a finished game");
+        let player = board.next_player();
+        let children: Vec<_> = moves
+            .iter()
+            .map(|&mv| {
+                let mut child = board.clone();
+                child.play(mv);
+                child
+            })
+            .collect();
+        let mut scores = vec![(0., 0); moves.len()];
+        for i in 0..(self.rollouts as usize).max(moves.len()) {
+            if timed_out(self.deadline) {
+                break;
+            }
+            let (total, games) = &mut scores[i % moves.len()];
+            *total += playout(&children[i % moves.len()], player, &mut self.rng);
+            *games += 1;
+        }
+        let average = |&(total, games): &(f32, u32)| total / games.max(1) as f32;
+        let best = (0..moves.len())
+            .filter(|&i| scores[i].1 > 0)
+            .max_by(|&i, &j| average(&scores[i]).total_cmp(&average(&scores[j])));
+        moves[best.unwrap_or(0)]
+    }
+}
+
+struct Node<const P: usize> {
+    board: OwareBoard<P>,
+    /// Move leading here from the parent, and who played it
+    mv: usize,
+    mover: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<usize>,
+    visits: u32,
+    /// Sum of the playout scores for `mover`
+    score: f32,
+}
+
+impl<const P: usize> Node<P> {
+    fn new(board: OwareBoard<P>, mv: usize, mover: Player, parent: Option<usize>) -> Self {
+        Self {
+            untried: moves(&board),
+            board,
+            mv,
+            mover,
+            parent,
+            children: vec![],
+            visits: 0,
+            score: 0.,
+        }
+    }
+}
+
+/// Monte Carlo tree search with `iterations` playouts, balancing the moves that did well
+/// against those tried less with the `exploration` weight of UCT
+///
+/// When a deadline is set the search stops there, and the most visited move so far is
+/// picked.
+#[derive(Debug)]
+pub struct MctsBot<R> {
+    iterations: u64,
+    exploration: f32,
+    deadline: Option<Instant>,
+    rng: R,
+}
+
+impl<R: Rng> MctsBot<R> {
+    pub fn new(iterations: u64, exploration: f32, rng: R) -> Self {
+        Self {
+            iterations,
+            exploration,
+            deadline: None,
+            rng,
+        }
+    }
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+    /// Child of `node` with the highest upper confidence bound
+    fn select<const P: usize>(&self, tree: &[Node<P>], node: usize) -> usize {
+        let ln = (tree[node].visits as f32).ln();
+        let uct = |&c: &usize| {
+            let child = &tree[c];
+            let visits = child.visits as f32;
+            child.score / visits + self.exploration * (ln / visits).sqrt()
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .expect("expanded nodes have children")
+    }
+}
+
+impl<const P: usize, R: Rng + std::fmt::Debug> Bot<OwareBoard<P>> for MctsBot<R> {
+    fn select_move(&mut self, board: &OwareBoard<P>) -> usize {
+        let first = *moves(board)
+            .first()
+            .expect("select_move called on a finished game");
+        let mut tree = vec![Node::new(board.clone(), first, board.next_player().other(), None)];
+        for _ in 0..self.iterations.max(1) {
+            if timed_out(self.deadline) {
+                break;
+            }
+            let mut node = 0;
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select(&tree, node);
+            }
+            let untried = tree[node].untried.len();
+            if untried > 0 {
+                let mv = tree[node].untried.swap_remove(self.rng.gen_range(0..untried));
+                let mut child = tree[node].board.clone();
+                let mover = child.next_player();
+                child.play(mv);
+                tree.push(Node::new(child, mv, mover, Some(node)));
+                let id = tree.len() - 1;
+                tree[node].children.push(id);
+                node = id;
+            }
+            let won = playout(&tree[node].board, Player::A, &mut self.rng);
+            let mut at = Some(node);
+            while let Some(n) = at {
+                let node = &mut tree[n];
+                node.visits += 1;
+                node.score += if node.mover == Player::A { won } else { 1. - won };
+                at = node.parent;
+            }
+        }
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|&&c| tree[c].visits)
+            .map_or(first, |&c| tree[c].mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::oware::Rules;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(5)
+    }
+    fn start() -> OwareBoard<3> {
+        OwareBoard::with_rules(3, Rules::default())
+    }
+    /// Every position of a random game on the small board
+    fn positions() -> Vec<OwareBoard<3>> {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut board = start();
+        let mut positions = vec![];
+        while !board.is_done() {
+            positions.push(board.clone());
+            board.play(*moves(&board).choose(&mut rng).unwrap());
+        }
+        positions
+    }
+
+    #[test]
+    fn bots_pick_legal_moves() {
+        for board in positions() {
+            let picks = [
+                RolloutBot::new(27, rng()).select_move(&board),
+                MctsBot::new(81, 1., rng()).select_move(&board),
+            ];
+            for mv in picks {
+                assert!(board.is_available_move(mv), "{mv} on {board:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn bots_stop_at_the_deadline() {
+        let board = start();
+        let budget = Duration::from_millis(100);
+        // far more playouts than fit in the budget
+        let timed = |select: &mut dyn FnMut(Instant) -> usize| {
+            let started = Instant::now();
+            let mv = select(started + budget);
+            assert!(board.is_available_move(mv));
+            assert!(started.elapsed() < budget + Duration::from_millis(250));
+        };
+        timed(&mut |deadline| {
+            RolloutBot::new(u32::MAX, rng())
+                .with_deadline(deadline)
+                .select_move(&board)
+        });
+        timed(&mut |deadline| {
+            MctsBot::new(u64::MAX, 1., rng())
+                .with_deadline(deadline)
+                .select_move(&board)
+        });
+    }
+}
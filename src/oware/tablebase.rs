@@ -0,0 +1,357 @@
+//! Endgame tablebase: perfect play for positions with few seeds left on the board
+//!
+//! Positions are seen from the player to move, whose pits come first, and only the seeds
+//! on the board matter. The table holds how many more of those seeds the player to move
+//! ends up with than the opponent under perfect play, so its sign added to the current
+//! score difference tells the outcome.
+//!
+//! Values come from retrograde analysis, one seed count at a time. Captures lead to
+//! positions with fewer seeds, which are solved already, while quiet moves keep the seed
+//! count. Positions with the same count are solved together, starting from the split the
+//! game ends with after `QUIET_LIMIT` quiet plies and adding one ply per round until no
+//! value changes. Values are exact right after a capture, and assume the whole quiet limit
+//! is ahead otherwise.
+//!
+//! # File format
+//!
+//! The `OWTB` magic, then one byte each for the format version, the pits per side, the
+//! rules as an index into `Rules::ALL` and the most seeds covered. After that a signed byte
+//! per position, from 0 seeds on the board up to the most, and for each seed count in the
+//! lexicographic order of the seeds per pit, mover's pits first.
+use std::path::Path;
+use std::sync::OnceLock;
+
+use bevy::log::{info, warn};
+use board_game::board::{Board, Player};
+
+use super::board::QUIET_LIMIT;
+use super::{OwareBoard, Rules};
+
+const MAGIC: &[u8; 4] = b"OWTB";
+const VERSION: u8 = 1;
+const HEADER: usize = 8;
+
+pub struct Tablebase {
+    pits: usize,
+    rules: Rules,
+    /// `values[n][rank]` of the positions with `n` seeds on the board
+    values: Vec<Vec<i8>>,
+    /// `counts[parts][seeds]` ways to spread `seeds` over `parts` pits
+    counts: Vec<Vec<usize>>,
+}
+
+fn counts(parts: usize, max_seeds: usize) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; max_seeds + 1]; parts + 1];
+    counts[0][0] = 1;
+    for k in 1..=parts {
+        for m in 0..=max_seeds {
+            // the first pit is either empty, or holds a seed on top of any arrangement
+            let filled = m.checked_sub(1).map_or(0, |m| counts[k][m]);
+            counts[k][m] = counts[k - 1][m] + filled;
+        }
+    }
+    counts
+}
+
+/// Seeds of the player to move keeping their own side at the end of the game
+fn split(seeds: &[u8]) -> i8 {
+    let (mine, theirs) = seeds.split_at(seeds.len() / 2);
+    let side = |s: &[u8]| s.iter().map(|&s| s as i8).sum::<i8>();
+    side(mine) - side(theirs)
+}
+
+impl Tablebase {
+    /// Where `Ai::Endgame` bots look for their table, relative to the working directory
+    pub const FILE: &str = "oware.tb";
+
+    /// Solves every position of `OwareBoard<P>` with up to `max_seeds` seeds on the board,
+    /// calling `done` with each seed count and its number of positions once solved
+    pub fn generate<const P: usize>(
+        rules: Rules,
+        max_seeds: u8,
+        mut done: impl FnMut(u8, usize),
+    ) -> Self {
+        assert!(
+            matches!(rules, Rules::Oware(_)),
+            "the tablebase needs turns that alternate"
+        );
+        let mut table = Self {
+            pits: P,
+            rules,
+            values: vec![],
+            counts: counts(2 * P, max_seeds as usize),
+        };
+        for n in 0..=max_seeds {
+            let total = table.counts[2 * P][n as usize];
+            // best capture, or the end of the game, and the positions quiet moves lead to
+            let mut fixed = Vec::with_capacity(total);
+            let mut quiet = Vec::with_capacity(total);
+            let mut values = Vec::with_capacity(total);
+            for rank in 0..total {
+                let seeds = table.unrank(n as usize, rank);
+                let board = table.board::<P>(&seeds);
+                let mut best = None;
+                let mut next = vec![];
+                for mv in (0..P).filter(|&mv| board.is_available_move(mv)) {
+                    let (gain, child) = Self::after(&board, mv);
+                    if child.iter().sum::<u8>() == n {
+                        next.push(table.rank(&child) as u32);
+                    } else {
+                        let value = table.value(&child).expect("fewer seeds are solved first");
+                        best = best.max(Some(gain - value));
+                    }
+                }
+                if best.is_none() && next.is_empty() {
+                    best = Some(split(&seeds));
+                }
+                fixed.push(best);
+                quiet.push(next);
+                values.push(split(&seeds));
+            }
+            for _ in 0..QUIET_LIMIT {
+                let next: Vec<i8> = fixed
+                    .iter()
+                    .zip(&quiet)
+                    .map(|(&best, quiet)| {
+                        let quiet = quiet.iter().map(|&r| -values[r as usize]).max();
+                        quiet.max(best).expect("positions without moves are fixed")
+                    })
+                    .collect();
+                if next == values {
+                    break;
+                }
+                values = next;
+            }
+            table.values.push(values);
+            done(n, total);
+        }
+        table
+    }
+    /// The table in `Tablebase::FILE`, read on first use
+    pub fn shared() -> Option<&'static Self> {
+        static SHARED: OnceLock<Option<Tablebase>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| match Self::load(Self::FILE) {
+                Ok(table) => {
+                    info!(
+                        "Loaded the endgame tablebase for {} seeds on the board",
+                        table.max_seeds()
+                    );
+                    Some(table)
+                }
+                Err(e) => {
+                    warn!("No endgame tablebase in {}: {e}", Self::FILE);
+                    None
+                }
+            })
+            .as_ref()
+    }
+    pub fn max_seeds(&self) -> u8 {
+        (self.values.len() - 1) as u8
+    }
+    /// The move leaving the player to move with the most seeds, if the table covers it
+    pub fn best_move<const P: usize>(&self, board: &OwareBoard<P>) -> Option<usize> {
+        if P != self.pits || board.rules() != self.rules || board.is_done() {
+            return None;
+        }
+        let seeds = Self::seeds(board);
+        self.value(&seeds)?;
+        let board = self.board::<P>(&seeds);
+        let values = (0..P)
+            .filter(|&mv| board.is_available_move(mv))
+            .map(|mv| {
+                let (gain, child) = Self::after(&board, mv);
+                Some((gain - self.value(&child)?, mv))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        values.into_iter().max_by_key(|&(value, _)| value).map(|(_, mv)| mv)
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let rules = Rules::ALL.iter().position(|&r| r == self.rules).unwrap();
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, self.pits as u8, rules as u8, self.max_seeds()]);
+        bytes.extend(self.values.iter().flatten().map(|&v| v as u8));
+        bytes
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER || &bytes[..4] != MAGIC {
+            return Err("not an oware tablebase".to_string());
+        }
+        let [version, pits, rules, max_seeds] = [bytes[4], bytes[5], bytes[6], bytes[7]];
+        if version != VERSION {
+            return Err(format!("unsupported tablebase version {version}"));
+        }
+        let rules = *Rules::ALL
+            .get(rules as usize)
+            .ok_or_else(|| format!("unknown rules {rules}"))?;
+        let (pits, max_seeds) = (pits as usize, max_seeds as usize);
+        let counts = counts(2 * pits, max_seeds);
+        let expected: usize = counts[2 * pits].iter().sum();
+        if bytes.len() != HEADER + expected {
+            return Err(format!(
+                "expected {expected} positions, found {}",
+                bytes.len() - HEADER
+            ));
+        }
+        let mut body = &bytes[HEADER..];
+        let values = counts[2 * pits]
+            .iter()
+            .map(|&total| {
+                let (level, rest) = body.split_at(total);
+                body = rest;
+                level.iter().map(|&v| v as i8).collect()
+            })
+            .collect();
+        Ok(Self {
+            pits,
+            rules,
+            values,
+            counts,
+        })
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_bytes(&std::fs::read(path).map_err(|e| e.to_string())?)
+    }
+
+    /// Seeds per pit, the player to move's first
+    fn seeds<const P: usize>(board: &OwareBoard<P>) -> Vec<u8> {
+        let me = board.next_player();
+        [me, me.other()]
+            .into_iter()
+            .flat_map(|p| (0..P).map(move |mv| board.get_seeds(p, mv)))
+            .collect()
+    }
+    /// Position with `A` to move and empty stores
+    fn board<const P: usize>(&self, seeds: &[u8]) -> OwareBoard<P> {
+        let mut pits = [[0; P]; 2];
+        for (i, &s) in seeds.iter().enumerate() {
+            pits[i / P][i % P] = s;
+        }
+        OwareBoard::with_pits(self.rules, pits, Player::A)
+    }
+    /// Net seeds `A` gains with the move, and the position left to `B`
+    fn after<const P: usize>(board: &OwareBoard<P>, mv: usize) -> (i8, Vec<u8>) {
+        let child = board.sow(mv);
+        let gain = child.score(Player::A) as i8 - child.score(Player::B) as i8;
+        (gain, Self::seeds(&child))
+    }
+    fn value(&self, seeds: &[u8]) -> Option<i8> {
+        let n: usize = seeds.iter().map(|&s| s as usize).sum();
+        self.values.get(n).map(|level| level[self.rank(seeds)])
+    }
+    /// Index of `seeds` among the arrangements of as many seeds, in lexicographic order
+    fn rank(&self, seeds: &[u8]) -> usize {
+        let mut left: usize = seeds.iter().map(|&s| s as usize).sum();
+        let mut rank = 0;
+        for (i, &s) in seeds.iter().enumerate() {
+            let parts = &self.counts[seeds.len() - i];
+            // arrangements with fewer seeds in this pit come first
+            rank += parts[left] - parts[left - s as usize];
+            left -= s as usize;
+        }
+        rank
+    }
+    fn unrank(&self, seeds: usize, mut rank: usize) -> Vec<u8> {
+        let mut left = seeds;
+        (1..=2 * self.pits)
+            .rev()
+            .map(|parts| {
+                let parts = &self.counts[parts];
+                let mut s = 0;
+                while s < left && parts[left] - parts[left - s - 1] <= rank {
+                    s += 1;
+                }
+                rank -= parts[left] - parts[left - s];
+                left -= s;
+                s as u8
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use board_game::{ai::Bot, board::Outcome};
+
+    use super::*;
+    use crate::oware::minmax::MinMaxBot;
+
+    fn table() -> Tablebase {
+        Tablebase::generate::<3>(Rules::default(), 5, |_, _| ())
+    }
+
+    /// Whether the player to move wins, draws or loses with perfect play, when the player
+    /// has a move and every line ends within `depth` plies
+    fn solve<const P: usize>(board: &OwareBoard<P>, depth: u32) -> Option<Ordering> {
+        if let Some(outcome) = board.outcome() {
+            return Some(match outcome {
+                Outcome::Draw => Ordering::Equal,
+                Outcome::WonBy(p) if p == board.next_player() => Ordering::Greater,
+                Outcome::WonBy(_) => Ordering::Less,
+            });
+        }
+        let depth = depth.checked_sub(1)?;
+        let mut best = None;
+        for mv in (0..P).filter(|&mv| board.is_available_move(mv)) {
+            let mut child = board.clone();
+            child.play(mv);
+            best = best.max(Some(solve(&child, depth)?.reverse()));
+        }
+        best
+    }
+
+    #[test]
+    fn rank_round_trips() {
+        let table = table();
+        for n in 0..=5 {
+            let mut previous = None;
+            for rank in 0..table.counts[6][n] {
+                let seeds = table.unrank(n, rank);
+                assert_eq!(seeds.iter().map(|&s| s as usize).sum::<usize>(), n);
+                assert_eq!(table.rank(&seeds), rank, "{seeds:?}");
+                assert!(previous < Some(seeds.clone()), "{seeds:?} out of order");
+                previous = Some(seeds);
+            }
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let table = table();
+        let bytes = table.to_bytes();
+        let read = Tablebase::from_bytes(&bytes).unwrap();
+        assert_eq!((read.pits, read.rules), (3, Rules::default()));
+        assert_eq!(read.values, table.values);
+        assert_eq!(read.to_bytes(), bytes);
+        assert!(Tablebase::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Tablebase::from_bytes(b"OWTX\x01\x03\x00\x05").is_err());
+    }
+
+    #[test]
+    fn agrees_with_minmax_on_short_endgames() {
+        let table = table();
+        let mut checked = 0;
+        for n in 1..=5 {
+            for rank in 0..table.counts[6][n] {
+                let seeds = table.unrank(n, rank);
+                let board = table.board::<3>(&seeds);
+                let Some(exact) = solve(&board, 8) else {
+                    continue;
+                };
+                // the stores are empty, so the table's value decides the outcome
+                assert_eq!(table.value(&seeds).unwrap().cmp(&0), exact, "{seeds:?}");
+                let mv = MinMaxBot::new(8).select_move(&board);
+                let (gain, child) = Tablebase::after(&board, mv);
+                let value = gain - table.value(&child).unwrap();
+                assert_eq!(value.cmp(&0), exact, "{seeds:?} after {mv}");
+                checked += 1;
+            }
+        }
+        assert!(checked > 0, "no position ends within 8 plies");
+    }
+}
@@ -0,0 +1,176 @@
+//! User settings kept between launches
+//!
+//! Stored as `key = value` lines, in `oware/settings.txt` under the platform config
+//! directory on native, and in `localStorage` on the web:
+//!
+//! ```text
+//! first = human
+//! second = minmax:8
+//! seeds = 4
+//! pits = 6
+//! rules = Abapa
+//! clock = fischer:300:3
+//! animation = fast
+//! bot-delay = 729
+//! volume = 50
+//! ```
+//!
+//! Unknown keys and invalid values are skipped, keeping their defaults, as are seeds too
+//! many for the pits.
+use crate::{launch::Launch, menu::OwareCfg, oware::{seeds_range, PITS}, GameState};
+use bevy::prelude::*;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY: &str = "oware-settings";
+
+impl OwareCfg {
+    /// The persistent part of the configuration, as written to the settings file
+    pub fn settings(&self) -> String {
+        let entries: [(&str, &dyn Display); 15] = [
+            ("first", &self.actors[0]),
+            ("second", &self.actors[1]),
+            ("seeds", &self.init_seeds),
+            ("pits", &self.new_pits),
+            ("rules", &self.rules),
+            ("hint", &self.hint),
+            ("custom-rollouts", &self.custom.rollouts),
+            ("custom-iterations", &self.custom.iterations),
+            ("custom-exploration", &self.custom.exploration),
+            ("moves", &self.show_moves),
+            ("clock", &self.time_control),
+            ("animation", &self.animation),
+            ("bot-delay", &self.bot_delay),
+            ("volume", &self.volume),
+            ("mute", &self.muted),
+        ];
+        entries
+            .iter()
+            .map(|(key, value)| format!("{key} = {value}\n"))
+            .collect()
+    }
+    /// Override fields with the settings found in `text`
+    pub fn apply_settings(&mut self, text: &str) {
+        fn set<T: FromStr>(field: &mut T, value: &str) -> Result<(), String> {
+            *field = value.parse().map_err(|_| format!("invalid value {value:?}"))?;
+            Ok(())
+        }
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("Skipping setting {line:?}");
+                continue;
+            };
+            let value = value.trim();
+            let applied = match key.trim() {
+                "first" => set(&mut self.actors[0], value),
+                "second" => set(&mut self.actors[1], value),
+                "seeds" => set(&mut self.init_seeds, value),
+                "pits" => value
+                    .parse()
+                    .ok()
+                    .filter(|p| PITS.contains(p))
+                    .map(|p| (self.pits, self.new_pits) = (p, p))
+                    .ok_or_else(|| format!("invalid value {value:?}")),
+                "rules" => set(&mut self.rules, value),
+                "hint" => set(&mut self.hint, value),
+                "custom-rollouts" => set(&mut self.custom.rollouts, value),
+                "custom-iterations" => set(&mut self.custom.iterations, value),
+                "custom-exploration" => set(&mut self.custom.exploration, value),
+                "moves" => set(&mut self.show_moves, value),
+                "clock" => set(&mut self.time_control, value),
+                "animation" => set(&mut self.animation, value),
+                "bot-delay" => set(&mut self.bot_delay, value),
+                "volume" => set(&mut self.volume, value),
+                "mute" => set(&mut self.muted, value),
+                key => Err(format!("unknown key {key:?}")),
+            };
+            if let Err(e) = applied {
+                warn!("Skipping setting {line:?}: {e}");
+            }
+        }
+        // `pits` may come after `seeds`, so the seeds are checked once both are read
+        if !seeds_range(self.pits).contains(&self.init_seeds) {
+            warn!("Skipping {} seeds per pit, too many for {} pits", self.init_seeds, self.pits);
+            self.init_seeds = Self::default().init_seeds;
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path() -> Option<std::path::PathBuf> {
+    Some(dirs::config_dir()?.join("oware").join("settings.txt"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read() -> Option<String> {
+    std::fs::read_to_string(path()?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(text: &str) {
+    let Some(path) = path() else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, text));
+    if let Err(e) = written {
+        error!("Could not save settings to {}: {e}", path.display());
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read() -> Option<String> {
+    storage()?.get_item(STORAGE_KEY).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(text: &str) {
+    if let Some(Err(e)) = storage().map(|s| s.set_item(STORAGE_KEY, text)) {
+        error!("Could not save settings: {e:?}");
+    }
+}
+
+/// Start from the saved settings, with the setup given at launch on top
+fn load_settings(mut commands: Commands, launch: Option<Res<Launch>>) {
+    let mut cfg = OwareCfg::default();
+    if let Some(text) = read() {
+        cfg.apply_settings(&text);
+    }
+    if let Some(launch) = launch {
+        launch.apply(&mut cfg);
+    }
+    commands.insert_resource(cfg);
+}
+
+/// Write the settings back when they differ from what was last loaded or saved
+fn save_settings(cfg: Res<OwareCfg>, mut saved: Local<Option<String>>) {
+    let text = cfg.settings();
+    match saved.as_ref() {
+        None => *saved = Some(text),
+        Some(last) if *last == text => (),
+        Some(_) => {
+            write(&text);
+            *saved = Some(text);
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(load_settings.in_schedule(OnEnter(GameState::Loading)))
+            .add_system(
+                save_settings.run_if(
+                    resource_exists::<OwareCfg>().and_then(resource_changed::<OwareCfg>()),
+                ),
+            );
+    }
+}
@@ -10,7 +10,7 @@ pub struct BeTween<T> {
 }
 impl<T: Clone + Send + Sync + 'static> BeTween<T> {
     /// Construct a lens from a pair of getter functions
-    pub fn _new<U>(lerp: U) -> Self
+    pub fn new<U>(lerp: U) -> Self
     where
         U: Fn(&mut T, &T, f32) + Send + Sync + 'static,
     {
@@ -51,29 +51,30 @@ pub fn vis_seq(duration: std::time::Duration, show: bool) -> Tween<Visibility> {
         BeTween::with_lerp(move |c: &mut Visibility, _, r| c.is_visible = show ^ (r < 0.5)),
     )
 }
-pub fn shake_seq(duration: std::time::Duration) -> Sequence<Transform> {
-    let tween = |s, e, i| {
+*/
+/// Rock a sprite side to side, each swing smaller than the one before
+pub fn shake_seq(duration: Duration) -> Sequence<Transform> {
+    let tween = |s: f32, e: f32, i: u32| {
         Tween::new(
             EaseFunction::ElasticInOut,
             duration * i / 3,
-            BeTween::with_lerp(move |c: &mut Transform, _, r| {
+            BeTween::new(move |c: &mut Transform, _, r| {
                 c.rotation = Quat::from_rotation_z(s + (e - s) * r)
             }),
         )
     };
     let pi = std::f32::consts::PI;
     Sequence::new((1..4).rev().map(|i| {
-        tween(0., pi / 12. / i as f32, i)
-            .then(tween(-pi / 12. / i as f32, -pi / 12. / i as f32, i))
-            .then(tween(-pi / 12. / i as f32, 0., i))
+        let a = pi / 12. / i as f32;
+        tween(0., a, i).then(tween(a, -a, i)).then(tween(-a, 0., i))
     }))
 }
-*/
 pub struct GameTweeningPlugin;
 
 impl Plugin for GameTweeningPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(TweeningPlugin)
+            .add_system(component_animator_system::<Sprite>)
             .add_system(component_animator_system::<Text>)
             .add_system(component_animator_system::<Transform>);
     }